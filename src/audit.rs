@@ -0,0 +1,524 @@
+use crate::command_model_layer::CommandModelServiceError;
+use crate::line_writer::LineWriter;
+use crate::request::AsInteraction;
+use crate::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tower::{Layer, Service};
+use twilight_model::application::interaction::application_command::{
+    CommandDataOption, CommandOptionValue,
+};
+use twilight_model::application::interaction::{Interaction, InteractionData, InteractionType};
+use twilight_model::id::marker::{GuildMarker, UserMarker};
+use twilight_model::id::Id;
+
+/// Value recorded in place of redacted options.
+pub const REDACTED: &str = "[redacted]";
+
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Error(String),
+}
+
+/// One audited command invocation.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Milliseconds since the Unix epoch at which the command was received.
+    pub timestamp: u64,
+    pub user_id: Option<Id<UserMarker>>,
+    pub guild_id: Option<Id<GuildMarker>>,
+    /// Command name followed by the names of the invoked subcommand group and subcommand.
+    pub command: Vec<String>,
+    pub options: BTreeMap<String, Value>,
+    pub outcome: AuditOutcome,
+}
+
+/// Destination of [`AuditRecord`]s.
+///
+/// Sinks must not fail, errors writing a record should be handled or ignored by the sink.
+pub trait AuditSink: Send + Sync {
+    fn write(&self, record: &AuditRecord);
+}
+
+/// Writes every record as a JSON line to a writer on a background thread, ignoring write errors.
+#[derive(Debug)]
+pub struct JsonLinesSink {
+    writer: LineWriter,
+}
+
+impl JsonLinesSink {
+    #[must_use]
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        JsonLinesSink {
+            writer: LineWriter::new(writer),
+        }
+    }
+
+    /// Appends records to the file at `path`, creating it if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be opened.
+    pub fn append(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(JsonLinesSink::new(file))
+    }
+
+    /// Waits until all records written so far reached the writer.
+    pub async fn flush(&self) {
+        self.writer.flush().await;
+    }
+}
+
+impl AuditSink for JsonLinesSink {
+    fn write(&self, record: &AuditRecord) {
+        if let Ok(line) = serde_json::to_vec(record) {
+            self.writer.write_line(line);
+        }
+    }
+}
+
+/// Keeps records in memory, shared between clones.
+#[derive(Clone, Default, Debug)]
+pub struct MemorySink {
+    records: Arc<Mutex<Vec<AuditRecord>>>,
+}
+
+impl MemorySink {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn records(&self) -> Vec<AuditRecord> {
+        self.records
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl AuditSink for MemorySink {
+    fn write(&self, record: &AuditRecord) {
+        self.records
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(record.clone());
+    }
+}
+
+/// Writes an [`AuditRecord`] for every command handled by the wrapped service.
+///
+/// To only audit some routes of a router, register them with
+/// [`CommandRouterService::route_audited`](crate::routing::command_router::CommandRouterService::route_audited)
+/// instead of wrapping the router.
+#[derive(Clone)]
+pub struct AuditLayer {
+    sink: Arc<dyn AuditSink>,
+    redacted: Arc<[String]>,
+}
+
+impl Debug for AuditLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditLayer")
+            .field("redacted", &self.redacted)
+            .finish_non_exhaustive()
+    }
+}
+
+// Manually implement PartialEq because sinks can only be compared by identity
+impl PartialEq for AuditLayer {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(Arc::as_ptr(&self.sink), Arc::as_ptr(&other.sink))
+            && self.redacted == other.redacted
+    }
+}
+
+impl Eq for AuditLayer {}
+
+impl AuditLayer {
+    #[must_use]
+    pub fn new(sink: impl AuditSink + 'static) -> Self {
+        AuditLayer {
+            sink: Arc::new(sink),
+            redacted: Arc::new([]),
+        }
+    }
+
+    /// Records the values of options named `name` as [`REDACTED`].
+    #[must_use]
+    pub fn redact(self, name: impl Into<String>) -> Self {
+        let mut redacted = self.redacted.to_vec();
+        redacted.push(name.into());
+
+        AuditLayer {
+            redacted: redacted.into(),
+            ..self
+        }
+    }
+
+    pub(crate) fn start(&self, interaction: &Interaction) -> Option<PendingAudit> {
+        // Autocomplete interactions also carry command data, but aren't invocations
+        if interaction.kind != InteractionType::ApplicationCommand {
+            return None;
+        }
+        let Some(InteractionData::ApplicationCommand(data)) = &interaction.data else {
+            return None;
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| {
+                u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)
+            });
+
+        let mut command = vec![data.name.clone()];
+        let mut options = BTreeMap::new();
+        self.collect_options(&data.options, &mut command, &mut options);
+
+        Some(PendingAudit {
+            sink: Arc::clone(&self.sink),
+            timestamp,
+            user_id: interaction.author_id(),
+            guild_id: interaction.guild_id,
+            command,
+            options,
+        })
+    }
+
+    fn collect_options(
+        &self,
+        data_options: &[CommandDataOption],
+        command: &mut Vec<String>,
+        options: &mut BTreeMap<String, Value>,
+    ) {
+        for option in data_options {
+            let value = match &option.value {
+                CommandOptionValue::SubCommand(sub_options)
+                | CommandOptionValue::SubCommandGroup(sub_options) => {
+                    command.push(option.name.clone());
+                    self.collect_options(sub_options, command, options);
+                    continue;
+                }
+                _ if self.redacted.contains(&option.name) => Value::from(REDACTED),
+                CommandOptionValue::Attachment(id) => Value::from(id.to_string()),
+                CommandOptionValue::Boolean(value) => Value::from(*value),
+                CommandOptionValue::Channel(id) => Value::from(id.to_string()),
+                CommandOptionValue::Focused(value, _) | CommandOptionValue::String(value) => {
+                    Value::from(value.as_str())
+                }
+                CommandOptionValue::Integer(value) => Value::from(*value),
+                CommandOptionValue::Mentionable(id) => Value::from(id.to_string()),
+                CommandOptionValue::Number(value) => Value::from(*value),
+                CommandOptionValue::Role(id) => Value::from(id.to_string()),
+                CommandOptionValue::User(id) => Value::from(id.to_string()),
+            };
+
+            options.insert(option.name.clone(), value);
+        }
+    }
+}
+
+pub(crate) struct PendingAudit {
+    sink: Arc<dyn AuditSink>,
+    timestamp: u64,
+    user_id: Option<Id<UserMarker>>,
+    guild_id: Option<Id<GuildMarker>>,
+    command: Vec<String>,
+    options: BTreeMap<String, Value>,
+}
+
+impl PendingAudit {
+    fn finish(self, outcome: AuditOutcome) {
+        self.sink.write(&AuditRecord {
+            timestamp: self.timestamp,
+            user_id: self.user_id,
+            guild_id: self.guild_id,
+            command: self.command,
+            options: self.options,
+            outcome,
+        });
+    }
+}
+
+fn error_chain(error: &dyn Display, mut source: Option<&dyn Error>) -> String {
+    let mut message = error.to_string();
+
+    while let Some(error) = source {
+        message.push_str(": ");
+        message.push_str(&error.to_string());
+        source = error.source();
+    }
+
+    message
+}
+
+fn outcome<Response, Error: std::error::Error>(result: &Result<Response, Error>) -> AuditOutcome {
+    match result {
+        Ok(_) => AuditOutcome::Success,
+        Err(error) => AuditOutcome::Error(error_chain(error, error.source())),
+    }
+}
+
+impl<TService> Layer<TService> for AuditLayer {
+    type Service = AuditService<TService>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        AuditService {
+            inner,
+            audit: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AuditService<Service> {
+    inner: Service,
+    audit: AuditLayer,
+}
+
+impl<TService, Request> Service<Request> for AuditService<TService>
+where
+    TService: Service<Request>,
+    TService::Response: Send + 'static,
+    TService::Error: Error + Send + 'static,
+    TService::Future: Send + 'static,
+    Request: AsInteraction,
+{
+    type Response = TService::Response;
+    type Error = TService::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let pending = self.audit.start(req.interaction());
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            let result = future.await;
+            if let Some(pending) = pending {
+                pending.finish(outcome(&result));
+            }
+            result
+        })
+    }
+}
+
+/// Like [`AuditService`], but also records the message of the route's inner service error.
+pub(crate) struct RouteAuditService<Service, ServiceError> {
+    inner: Service,
+    audit: AuditLayer,
+    describe: fn(&ServiceError) -> String,
+}
+
+// Manually implement Clone because ServiceError only appears in the describe function
+impl<Service: Clone, ServiceError> Clone for RouteAuditService<Service, ServiceError> {
+    fn clone(&self) -> Self {
+        RouteAuditService {
+            inner: self.inner.clone(),
+            audit: self.audit.clone(),
+            describe: self.describe,
+        }
+    }
+}
+
+impl<Service, ServiceError> RouteAuditService<Service, ServiceError> {
+    pub(crate) fn new(
+        inner: Service,
+        audit: AuditLayer,
+        describe: fn(&ServiceError) -> String,
+    ) -> Self {
+        RouteAuditService {
+            inner,
+            audit,
+            describe,
+        }
+    }
+}
+
+fn route_outcome<Response, ServiceError>(
+    result: &Result<Response, CommandModelServiceError<ServiceError>>,
+    describe: fn(&ServiceError) -> String,
+) -> AuditOutcome {
+    let Err(error) = result else {
+        return AuditOutcome::Success;
+    };
+
+    let source: Option<&dyn Error> = match error {
+        CommandModelServiceError::Service(inner) => {
+            return AuditOutcome::Error(format!("{error}: {}", describe(inner)));
+        }
+        CommandModelServiceError::Parse(source) => Some(source),
+        CommandModelServiceError::Invalid(source) => Some(source),
+        CommandModelServiceError::Unresolved(source) => Some(source),
+        CommandModelServiceError::NotACommand
+        | CommandModelServiceError::Timeout
+        | CommandModelServiceError::RateLimited
+        | CommandModelServiceError::WrongInstallation => None,
+    };
+
+    AuditOutcome::Error(error_chain(error, source))
+}
+
+impl<TService, Request, ServiceError> Service<Request> for RouteAuditService<TService, ServiceError>
+where
+    TService: Service<Request, Error = CommandModelServiceError<ServiceError>>,
+    TService::Response: Send + 'static,
+    TService::Future: Send + 'static,
+    ServiceError: Send + 'static,
+    Request: AsInteraction,
+{
+    type Response = TService::Response;
+    type Error = TService::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let pending = self.audit.start(req.interaction());
+        let future = self.inner.call(req);
+        let describe = self.describe;
+
+        Box::pin(async move {
+            let result = future.await;
+            if let Some(pending) = pending {
+                pending.finish(route_outcome(&result, describe));
+            }
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::audit::{
+        AuditLayer, AuditOutcome, AuditRecord, AuditSink, JsonLinesSink, MemorySink, REDACTED,
+    };
+    use crate::routing::command_router::CommandRouterService;
+    use crate::routing::command_service::command_service;
+    use crate::routing::route_options::RouteOptions;
+    use crate::test_utils;
+    use serde_json::Value;
+    use std::collections::BTreeMap;
+    use tower::ServiceExt;
+    use twilight_interactions::command::CommandModel;
+    use twilight_model::application::interaction::application_command::CommandOptionValue;
+    use twilight_model::application::interaction::{Interaction, InteractionType};
+    use twilight_model::id::Id;
+
+    #[derive(CommandModel)]
+    struct Ban {
+        #[allow(dead_code)]
+        reason: String,
+        days: i64,
+    }
+
+    #[tokio::test]
+    async fn audits_tagged_routes() {
+        async fn ban(_state: (), model: Ban) -> Result<(), &'static str> {
+            if model.days > 7 {
+                Err("too many days")
+            } else {
+                Ok(())
+            }
+        }
+
+        let sink = MemorySink::new();
+        let router = CommandRouterService::new(())
+            .route_audited(
+                Id::new(1),
+                command_service(ban),
+                RouteOptions::default(),
+                AuditLayer::new(sink.clone()).redact("reason"),
+            )
+            .route(Id::new(2), command_service(ban));
+
+        let ban = |days| Interaction {
+            guild_id: Some(Id::new(3)),
+            ..test_utils::command_interaction(
+                "ban",
+                &[
+                    ("reason", CommandOptionValue::String("secret".to_owned())),
+                    ("days", CommandOptionValue::Integer(days)),
+                ],
+            )
+        };
+
+        let interaction = ban(7);
+        let untagged = Interaction {
+            id: Id::new(2),
+            ..ban(7)
+        };
+        let autocomplete = Interaction {
+            kind: InteractionType::ApplicationCommandAutocomplete,
+            ..ban(7)
+        };
+        let failing = ban(30);
+
+        router.clone().oneshot(interaction).await.unwrap();
+        router.clone().oneshot(untagged).await.unwrap();
+        router.clone().oneshot(autocomplete).await.unwrap();
+        let _ = router.clone().oneshot(failing).await;
+        // Fails to parse because the options are missing
+        let _ = router.oneshot(test_utils::interaction(Id::new(1))).await;
+
+        let records = sink.records();
+        assert_eq!(records.len(), 3);
+
+        assert_eq!(records[0].guild_id, Some(Id::new(3)));
+        assert_eq!(records[0].command, ["ban"]);
+        assert_eq!(records[0].options["reason"], Value::from(REDACTED));
+        assert_eq!(records[0].options["days"], Value::from(7));
+        assert_eq!(records[0].outcome, AuditOutcome::Success);
+
+        assert_eq!(
+            records[1].outcome,
+            AuditOutcome::Error("Inner service error: too many days".to_owned())
+        );
+        assert!(matches!(records[2].outcome, AuditOutcome::Error(_)));
+    }
+
+    #[tokio::test]
+    async fn json_lines_sink() {
+        let buffer = test_utils::SharedBuffer::default();
+        let sink = JsonLinesSink::new(buffer.clone());
+        let record = AuditRecord {
+            timestamp: 1,
+            user_id: Some(Id::new(5)),
+            guild_id: None,
+            command: vec!["ban".to_owned()],
+            options: BTreeMap::new(),
+            outcome: AuditOutcome::Success,
+        };
+
+        sink.write(&record);
+        sink.write(&record);
+        sink.flush().await;
+
+        let written = buffer.contents();
+        let lines: Vec<AuditRecord> = String::from_utf8(written)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines, [record.clone(), record]);
+    }
+}
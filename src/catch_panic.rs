@@ -0,0 +1,175 @@
+use crate::BoxFuture;
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+type PanicHook = Arc<dyn Fn(&(dyn Any + Send)) + Send + Sync>;
+
+/// A handler panicked while handling an interaction.
+#[derive(Clone, Eq, PartialEq, Debug, thiserror::Error)]
+#[error("Handler panicked: {}", message.as_deref().unwrap_or("<non-string payload>"))]
+pub struct HandlerPanicked {
+    /// The panic message, if the payload was a string.
+    pub message: Option<String>,
+}
+
+impl HandlerPanicked {
+    fn from_payload(payload: &(dyn Any + Send)) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| (*message).to_owned())
+            .or_else(|| payload.downcast_ref::<String>().cloned());
+
+        HandlerPanicked { message }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, thiserror::Error)]
+pub enum CatchPanicError<ServiceError> {
+    #[error(transparent)]
+    Panicked(HandlerPanicked),
+    #[error("Inner service error")]
+    Service(ServiceError),
+}
+
+#[derive(Clone, Default)]
+pub struct CatchPanicLayer {
+    hook: Option<PanicHook>,
+}
+
+impl CatchPanicLayer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls `hook` with the payload of every caught panic.
+    #[must_use]
+    pub fn with_hook(hook: impl Fn(&(dyn Any + Send)) + Send + Sync + 'static) -> Self {
+        CatchPanicLayer {
+            hook: Some(Arc::new(hook)),
+        }
+    }
+}
+
+impl Debug for CatchPanicLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CatchPanicLayer")
+            .field("hook", &self.hook.is_some())
+            .finish()
+    }
+}
+
+impl<TService> Layer<TService> for CatchPanicLayer {
+    type Service = CatchPanicService<TService>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        CatchPanicService {
+            inner,
+            hook: self.hook.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CatchPanicService<Service> {
+    inner: Service,
+    hook: Option<PanicHook>,
+}
+
+impl<Service: Debug> Debug for CatchPanicService<Service> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CatchPanicService")
+            .field("inner", &self.inner)
+            .field("hook", &self.hook.is_some())
+            .finish()
+    }
+}
+
+fn caught(hook: Option<&PanicHook>, payload: &(dyn Any + Send)) -> HandlerPanicked {
+    if let Some(hook) = hook {
+        hook(payload);
+    }
+
+    HandlerPanicked::from_payload(payload)
+}
+
+impl<TService, Request> Service<Request> for CatchPanicService<TService>
+where
+    TService: Service<Request>,
+    TService::Response: Send + 'static,
+    TService::Error: Send + 'static,
+    TService::Future: Send + 'static,
+{
+    type Response = TService::Response;
+    type Error = CatchPanicError<TService::Error>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(CatchPanicError::Service)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let hook = self.hook.clone();
+
+        let mut future = match catch_unwind(AssertUnwindSafe(|| self.inner.call(req))) {
+            Ok(future) => Box::pin(future),
+            Err(payload) => {
+                let panicked = caught(hook.as_ref(), &*payload);
+                return Box::pin(std::future::ready(Err(CatchPanicError::Panicked(panicked))));
+            }
+        };
+
+        Box::pin(std::future::poll_fn(move |cx| {
+            match catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+                Ok(poll) => poll.map_err(CatchPanicError::Service),
+                Err(payload) => Poll::Ready(Err(CatchPanicError::Panicked(caught(
+                    hook.as_ref(),
+                    &*payload,
+                )))),
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::catch_panic::{CatchPanicError, CatchPanicLayer, HandlerPanicked};
+    use crate::routing::command_router::CommandRouterService;
+    use crate::routing::command_service::command_service;
+    use crate::test_utils::{self, Empty};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+    use twilight_model::id::Id;
+
+    #[tokio::test]
+    async fn catches_panics() {
+        async fn command(_state: (), _model: Empty) -> Result<i64, ()> {
+            panic!("oh no")
+        }
+
+        let hook_called = Arc::new(AtomicBool::new(false));
+        let hook_flag = Arc::clone(&hook_called);
+
+        let router = CommandRouterService::new(())
+            .route(Id::new(1), command_service(command))
+            .layer(CatchPanicLayer::with_hook(move |_payload| {
+                hook_flag.store(true, Ordering::Relaxed);
+            }));
+
+        let res = router.oneshot(test_utils::interaction(Id::new(1))).await;
+
+        assert_eq!(
+            res,
+            Err(CatchPanicError::Panicked(HandlerPanicked {
+                message: Some("oh no".to_owned())
+            }))
+        );
+        assert!(hook_called.load(Ordering::Relaxed));
+    }
+}
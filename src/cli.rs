@@ -0,0 +1,79 @@
+//! Helpers for a command definition export binary.
+//!
+//! ```no_run
+//! use twilight_model::application::command::Command;
+//!
+//! fn commands() -> Vec<Command> {
+//!     vec![]
+//! }
+//!
+//! fn main() -> std::process::ExitCode {
+//!     kubar_sparkles::cli::run(commands)
+//! }
+//! ```
+
+use crate::validate::validate_commands;
+use std::io::Write;
+use std::process::ExitCode;
+use twilight_model::application::command::Command;
+
+const USAGE: &str = "Usage: [--check] [--compact]
+
+Prints the command definitions as JSON and validates them against Discord's limits.
+
+  --check    Only validate, don't print the definitions
+  --compact  Print JSON on a single line";
+
+/// Prints the commands returned by `registry` as JSON to stdout and validation errors to stderr.
+///
+/// Exits with a failure code if any command is invalid, so this can run offline in CI.
+pub fn run(registry: impl FnOnce() -> Vec<Command>) -> ExitCode {
+    let mut check = false;
+    let mut compact = false;
+
+    for argument in std::env::args().skip(1) {
+        match argument.as_str() {
+            "--check" => check = true,
+            "--compact" => compact = true,
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ => {
+                eprintln!("Unknown argument {argument}\n\n{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let commands = registry();
+
+    if !check {
+        let json = if compact {
+            serde_json::to_string(&commands)
+        } else {
+            serde_json::to_string_pretty(&commands)
+        };
+
+        match json {
+            Ok(json) => {
+                let _ = writeln!(std::io::stdout(), "{json}");
+            }
+            Err(error) => {
+                eprintln!("Error serializing commands: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    match validate_commands(&commands) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{error}");
+            }
+            eprintln!("{} validation error(s)", errors.len());
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use crate::request::InteractionRequest;
+use crate::routing::component_router::custom_id;
+use crate::BoxFuture;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tower::{Layer, Service};
+use twilight_model::application::interaction::Interaction;
+use twilight_model::http::interaction::{InteractionResponse, InteractionResponseType};
+use twilight_model::id::marker::{MessageMarker, UserMarker};
+use twilight_model::id::Id;
+
+/// Selects which component interactions a collector receives.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct ComponentFilter {
+    pub message_id: Option<Id<MessageMarker>>,
+    pub user_id: Option<Id<UserMarker>>,
+    pub custom_id: Option<String>,
+    pub custom_id_prefix: Option<String>,
+}
+
+impl ComponentFilter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn message_id(mut self, message_id: Id<MessageMarker>) -> Self {
+        self.message_id = Some(message_id);
+        self
+    }
+
+    #[must_use]
+    pub fn user_id(mut self, user_id: Id<UserMarker>) -> Self {
+        self.user_id = Some(user_id);
+        self
+    }
+
+    #[must_use]
+    pub fn custom_id(mut self, custom_id: impl Into<String>) -> Self {
+        self.custom_id = Some(custom_id.into());
+        self
+    }
+
+    #[must_use]
+    pub fn custom_id_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.custom_id_prefix = Some(prefix.into());
+        self
+    }
+
+    #[must_use]
+    pub fn matches(&self, interaction: &Interaction) -> bool {
+        let Some(interaction_custom_id) = custom_id(interaction) else {
+            return false;
+        };
+
+        self.message_id.is_none_or(|message_id| {
+            interaction
+                .message
+                .as_ref()
+                .is_some_and(|message| message.id == message_id)
+        }) && self
+            .user_id
+            .is_none_or(|user_id| interaction.author_id() == Some(user_id))
+            && self
+                .custom_id
+                .as_ref()
+                .is_none_or(|custom_id| custom_id == interaction_custom_id)
+            && self
+                .custom_id_prefix
+                .as_ref()
+                .is_none_or(|prefix| interaction_custom_id.starts_with(prefix.as_str()))
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, thiserror::Error)]
+pub enum CollectorError {
+    #[error("No matching component interaction arrived in time")]
+    TimedOut,
+}
+
+/// A component interaction received by a collector.
+///
+/// The response passed to [`CollectedInteraction::respond`] is returned by the [`CollectorService`]
+/// that received the interaction. If no response is given, the interaction is acknowledged with a
+/// deferred update.
+#[derive(Debug)]
+pub struct CollectedInteraction {
+    pub interaction: Interaction,
+    responder: oneshot::Sender<InteractionResponse>,
+}
+
+impl CollectedInteraction {
+    pub fn respond(self, response: InteractionResponse) {
+        // The receiving service may have been dropped, in which case nobody is waiting anymore
+        let _ = self.responder.send(response);
+    }
+}
+
+struct PendingCollector {
+    id: u64,
+    filter: ComponentFilter,
+    sender: oneshot::Sender<CollectedInteraction>,
+}
+
+#[derive(Default)]
+struct CollectorRegistry {
+    next_id: u64,
+    pending: Vec<PendingCollector>,
+}
+
+/// Registry of handlers waiting for component interactions.
+///
+/// A [`CollectorLayer`] offers every component interaction to the registry before it is routed,
+/// and inserts the registry into the request extensions so handlers can wait for interactions.
+#[derive(Clone, Default)]
+pub struct Collectors {
+    registry: Arc<Mutex<CollectorRegistry>>,
+}
+
+impl Debug for Collectors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let registry = self.registry.lock().unwrap_or_else(PoisonError::into_inner);
+
+        f.debug_struct("Collectors")
+            .field("pending", &registry.pending.len())
+            .finish()
+    }
+}
+
+struct Deregister<'a> {
+    collectors: &'a Collectors,
+    id: u64,
+}
+
+impl Drop for Deregister<'_> {
+    fn drop(&mut self) {
+        let mut registry = self
+            .collectors
+            .registry
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        registry.pending.retain(|pending| pending.id != self.id);
+    }
+}
+
+impl Collectors {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Waits for the next component interaction matching `filter`.
+    ///
+    /// The collector is registered when this is called rather than when the returned future is
+    /// first polled, so interactions arriving in between are not missed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CollectorError::TimedOut`] if no matching interaction arrives within `timeout`.
+    pub fn wait_for(
+        &self,
+        filter: ComponentFilter,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<CollectedInteraction, CollectorError>> + Send + '_ {
+        let (sender, receiver) = oneshot::channel();
+
+        let id = {
+            let mut registry = self.registry.lock().unwrap_or_else(PoisonError::into_inner);
+            let id = registry.next_id;
+            registry.next_id += 1;
+            registry
+                .pending
+                .push(PendingCollector { id, filter, sender });
+            id
+        };
+        let deregister = Deregister {
+            collectors: self,
+            id,
+        };
+
+        async move {
+            let _deregister = deregister;
+
+            match tokio::time::timeout(timeout, receiver).await {
+                Ok(Ok(collected)) => Ok(collected),
+                Ok(Err(_)) | Err(_) => Err(CollectorError::TimedOut),
+            }
+        }
+    }
+
+    /// Hands `interaction` to the first waiting collector it matches.
+    ///
+    /// Returns a receiver for the collector's response, or `None` if no collector matched.
+    #[must_use]
+    pub fn offer(
+        &self,
+        interaction: &Interaction,
+    ) -> Option<oneshot::Receiver<InteractionResponse>> {
+        let mut registry = self.registry.lock().unwrap_or_else(PoisonError::into_inner);
+
+        while let Some(index) = registry
+            .pending
+            .iter()
+            .position(|pending| pending.filter.matches(interaction))
+        {
+            let pending = registry.pending.swap_remove(index);
+            let (responder, receiver) = oneshot::channel();
+
+            let collected = CollectedInteraction {
+                interaction: interaction.clone(),
+                responder,
+            };
+
+            // If sending fails the collector stopped waiting, so try the next one
+            if pending.sender.send(collected).is_ok() {
+                return Some(receiver);
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum CollectorOutcome<Response> {
+    Routed(Response),
+    /// The interaction was received by a collector, which responded with this response.
+    Collected(Box<InteractionResponse>),
+}
+
+#[derive(Clone, Debug)]
+pub struct CollectorLayer {
+    collectors: Collectors,
+}
+
+impl CollectorLayer {
+    #[must_use]
+    pub fn new(collectors: Collectors) -> Self {
+        CollectorLayer { collectors }
+    }
+}
+
+impl<TService> Layer<TService> for CollectorLayer {
+    type Service = CollectorService<TService>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        CollectorService {
+            inner,
+            collectors: self.collectors.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CollectorService<Service> {
+    inner: Service,
+    collectors: Collectors,
+}
+
+impl<TService, Request> Service<Request> for CollectorService<TService>
+where
+    TService: Service<InteractionRequest>,
+    TService::Response: Send + 'static,
+    TService::Error: Send + 'static,
+    TService::Future: Send + 'static,
+    Request: Into<InteractionRequest>,
+{
+    type Response = CollectorOutcome<TService::Response>;
+    type Error = TService::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let mut req = req.into();
+
+        if custom_id(&req.interaction).is_some() {
+            if let Some(receiver) = self.collectors.offer(&req.interaction) {
+                return Box::pin(async move {
+                    let response = receiver.await.unwrap_or(InteractionResponse {
+                        kind: InteractionResponseType::DeferredUpdateMessage,
+                        data: None,
+                    });
+
+                    Ok(CollectorOutcome::Collected(Box::new(response)))
+                });
+            }
+        }
+
+        req.extensions.insert(self.collectors.clone());
+        let future = self.inner.call(req);
+
+        Box::pin(async move { future.await.map(CollectorOutcome::Routed) })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::collector::{
+        CollectorError, CollectorLayer, CollectorOutcome, Collectors, ComponentFilter,
+    };
+    use crate::command_model_layer::CommandRequest;
+    use crate::error_renderer::ephemeral_response;
+    use crate::routing::command_router::CommandRouterService;
+    use crate::routing::command_service::command_service;
+    use crate::test_utils::{self, Empty};
+    use std::time::Duration;
+    use tower::{Layer, ServiceExt};
+    use twilight_model::id::Id;
+
+    #[tokio::test]
+    async fn handler_waits_for_component() {
+        async fn command(_state: (), request: CommandRequest<Empty>) -> Result<String, ()> {
+            let collectors = request.extension::<Collectors>().unwrap();
+
+            let collected = collectors
+                .wait_for(
+                    ComponentFilter::new().custom_id("confirm"),
+                    Duration::from_secs(5),
+                )
+                .await
+                .map_err(|_| ())?;
+            collected.respond(ephemeral_response("confirmed".to_owned()));
+
+            let timed_out = collectors
+                .wait_for(ComponentFilter::new(), Duration::from_millis(1))
+                .await;
+            assert_eq!(timed_out.unwrap_err(), CollectorError::TimedOut);
+
+            Ok("done".to_owned())
+        }
+
+        let router = CommandRouterService::new(()).route(Id::new(1), command_service(command));
+        let service = CollectorLayer::new(Collectors::new()).layer(router.interaction_router());
+
+        let (command_res, component_res) = tokio::join!(
+            service.clone().oneshot(test_utils::interaction(Id::new(1))),
+            async {
+                tokio::task::yield_now().await;
+                service
+                    .clone()
+                    .oneshot(test_utils::component_interaction("confirm"))
+                    .await
+            },
+        );
+
+        assert_eq!(
+            command_res,
+            Ok(CollectorOutcome::Routed(Some("done".to_owned())))
+        );
+        assert_eq!(
+            component_res,
+            Ok(CollectorOutcome::Collected(Box::new(ephemeral_response(
+                "confirmed".to_owned()
+            ))))
+        );
+    }
+}
@@ -1,15 +1,95 @@
+use crate::option_validation::InvalidOptions;
+use crate::request::{AsInteraction, Extensions, InteractionRequest};
+use crate::resolved::{ResolvedData, UnresolvedOption};
+use std::borrow::Cow;
 use std::future::Future;
 use std::marker::PhantomData;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use tower::{Layer, Service};
-use twilight_interactions::command::CommandModel;
+use twilight_interactions::command::{CommandInputData, CommandModel};
+use twilight_interactions::error::{ParseError, ParseOptionError, ParseOptionErrorType};
 use twilight_model::application::interaction::{Interaction, InteractionData};
 
+/// Input passed to command services after the interaction was parsed.
+///
+/// This is implemented for every [`CommandModel`], for [`CommandRequest`] which additionally
+/// gives access to the interaction and its extensions, and for
+/// [`Validated`](crate::option_validation::Validated) models.
+pub trait CommandInput: Sized {
+    type Model: CommandModel;
+
+    /// # Errors
+    ///
+    /// Returns an error if the parsed model is rejected, failing the call with
+    /// [`CommandModelServiceError::Invalid`].
+    fn from_request(
+        model: Self::Model,
+        request: InteractionRequest,
+    ) -> Result<Self, InvalidOptions>;
+}
+
+impl<TCommandModel: CommandModel> CommandInput for TCommandModel {
+    type Model = TCommandModel;
+
+    fn from_request(
+        model: Self::Model,
+        _request: InteractionRequest,
+    ) -> Result<Self, InvalidOptions> {
+        Ok(model)
+    }
+}
+
+/// A parsed command model together with the request it was parsed from.
+#[derive(Clone, Debug)]
+pub struct CommandRequest<CommandModel> {
+    pub model: CommandModel,
+    pub interaction: Interaction,
+    pub extensions: Extensions,
+}
+
+impl<CommandModel> AsInteraction for CommandRequest<CommandModel> {
+    fn interaction(&self) -> &Interaction {
+        &self.interaction
+    }
+
+    fn extensions_mut(&mut self) -> Option<&mut Extensions> {
+        Some(&mut self.extensions)
+    }
+}
+
+impl<CommandModel> CommandRequest<CommandModel> {
+    #[must_use]
+    pub fn extension<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.extensions.get()
+    }
+
+    /// Returns the entities referenced by the command options.
+    #[must_use]
+    pub fn resolved(&self) -> ResolvedData<'_> {
+        ResolvedData::from_interaction(&self.interaction)
+    }
+}
+
+impl<TCommandModel: CommandModel> CommandInput for CommandRequest<TCommandModel> {
+    type Model = TCommandModel;
+
+    fn from_request(
+        model: Self::Model,
+        request: InteractionRequest,
+    ) -> Result<Self, InvalidOptions> {
+        Ok(CommandRequest {
+            model,
+            interaction: request.interaction,
+            extensions: request.extensions,
+        })
+    }
+}
+
 // TODO: manually impl rest of derive traits
 #[derive(Ord, PartialOrd, Eq, PartialEq, Debug)]
 pub struct CommandModelLayer<CommandModel> {
-    phantom_data: PhantomData<CommandModel>,
+    phantom_data: PhantomData<fn() -> CommandModel>,
 }
 
 // Manually implement derive traits because CommandModel generic param should have no bearing on
@@ -56,13 +136,23 @@ pub enum CommandModelServiceError<ServiceError> {
     NotACommand,
     #[error("Inner service error")]
     Service(ServiceError),
+    #[error("Route timed out")]
+    Timeout,
+    #[error("Route was rate limited")]
+    RateLimited,
+    #[error("Route is not available in this context or installation")]
+    WrongInstallation,
+    #[error("Command options failed validation")]
+    Invalid(#[source] InvalidOptions),
+    #[error("Command option could not be resolved")]
+    Unresolved(#[source] UnresolvedOption),
 }
 
 // TODO: manually impl rest of derive traits
 #[derive(Ord, PartialOrd, Eq, PartialEq, Debug)]
 pub struct CommandModelLayerService<Service, CommandModel> {
     inner: Service,
-    phantom_data: PhantomData<CommandModel>,
+    phantom_data: PhantomData<fn() -> CommandModel>,
 }
 
 // Manually implement derive traits because CommandModel generic param should have no bearing on
@@ -78,14 +168,15 @@ impl<Service: Clone, CommandModel> Clone for CommandModelLayerService<Service, C
 
 impl<Service: Copy, CommandModel> Copy for CommandModelLayerService<Service, CommandModel> {}
 
-impl<TService, TCommandModel> Service<Interaction>
-    for CommandModelLayerService<TService, TCommandModel>
+impl<TService, TCommandInput, Request> Service<Request>
+    for CommandModelLayerService<TService, TCommandInput>
 where
-    TService: Service<TCommandModel> + Clone + Send + 'static,
+    TService: Service<TCommandInput> + Clone + Send + 'static,
     TService::Response: 'static,
     TService::Error: 'static,
     TService::Future: Send,
-    TCommandModel: CommandModel,
+    TCommandInput: CommandInput,
+    Request: Into<InteractionRequest>,
 {
     type Response = TService::Response;
     type Error = CommandModelServiceError<TService::Error>;
@@ -98,21 +189,43 @@ where
             .map_err(CommandModelServiceError::Service)
     }
 
-    fn call(&mut self, req: Interaction) -> Self::Future {
+    fn call(&mut self, req: Request) -> Self::Future {
         let clone = self.inner.clone();
         let mut inner = std::mem::replace(&mut self.inner, clone);
+        let req = req.into();
 
         Box::pin(async move {
-            let command_model = TCommandModel::from_interaction(match req.data {
-                Some(InteractionData::ApplicationCommand(command_data)) => (*command_data).into(),
-                _ => return Err(CommandModelServiceError::NotACommand),
-            })
-            .map_err(CommandModelServiceError::Parse)?;
-
-            inner
-                .call(command_model)
-                .await
-                .map_err(CommandModelServiceError::Service)
+            // The model may not be Send, so it must be gone before anything is awaited
+            let future = {
+                let parsed = TCommandInput::Model::from_interaction(match &req.interaction.data {
+                    Some(InteractionData::ApplicationCommand(command_data)) => CommandInputData {
+                        options: command_data.options.clone(),
+                        resolved: command_data.resolved.as_ref().map(Cow::Borrowed),
+                    },
+                    _ => return Err(CommandModelServiceError::NotACommand),
+                });
+
+                match parsed {
+                    Ok(command_model) => {
+                        let input = TCommandInput::from_request(command_model, req)
+                            .map_err(CommandModelServiceError::Invalid)?;
+
+                        inner.call(input)
+                    }
+                    Err(ParseError::Option(ParseOptionError {
+                        field,
+                        kind: ParseOptionErrorType::LookupFailed(id),
+                    })) => {
+                        return Err(CommandModelServiceError::Unresolved(UnresolvedOption {
+                            field,
+                            id,
+                        }))
+                    }
+                    Err(error) => return Err(CommandModelServiceError::Parse(error)),
+                }
+            };
+
+            future.await.map_err(CommandModelServiceError::Service)
         })
     }
 }
@@ -0,0 +1,324 @@
+use crate::collector::{CollectorError, Collectors, ComponentFilter};
+use crate::command_model_layer::CommandRequest;
+use crate::routing::component_router::{custom_id, CUSTOM_ID_SEPARATOR};
+use crate::session::generate_id;
+use crate::BoxFuture;
+use std::time::Duration;
+use twilight_model::channel::message::component::{ActionRow, Button, ButtonStyle};
+use twilight_model::channel::message::{Component, MessageFlags};
+use twilight_model::http::interaction::{
+    InteractionResponse, InteractionResponseData, InteractionResponseType,
+};
+use twilight_model::id::marker::UserMarker;
+use twilight_model::id::Id;
+
+/// Route name prefixing the custom ids of confirmation buttons.
+pub const CONFIRM_ROUTE: &str = "confirm";
+
+const CONFIRM_ACTION: &str = "yes";
+const CANCEL_ACTION: &str = "no";
+
+/// Responds to the interaction asking for confirmation and edits that response.
+pub trait MessageSender {
+    type Error;
+
+    /// Sends the initial response to the interaction.
+    fn respond(&self, response: InteractionResponse) -> BoxFuture<'_, Result<(), Self::Error>>;
+
+    /// Edits the message sent by [`MessageSender::respond`].
+    fn edit_response(
+        &self,
+        data: InteractionResponseData,
+    ) -> BoxFuture<'_, Result<(), Self::Error>>;
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Confirmation {
+    Confirmed,
+    Cancelled,
+    TimedOut,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfirmError<E> {
+    #[error("Request has no collectors, is the CollectorLayer applied?")]
+    MissingCollectors,
+    #[error("Interaction has no author")]
+    MissingAuthor,
+    #[error("Sending or editing the confirmation message failed")]
+    Send(#[source] E),
+}
+
+/// Messages shown on the buttons and after the dialog is resolved.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ConfirmMessages {
+    pub confirm: String,
+    pub cancel: String,
+    pub confirmed: String,
+    pub cancelled: String,
+    pub timed_out: String,
+}
+
+impl ConfirmMessages {
+    #[must_use]
+    pub fn english() -> Self {
+        ConfirmMessages {
+            confirm: "Confirm".to_owned(),
+            cancel: "Cancel".to_owned(),
+            confirmed: "Confirmed.".to_owned(),
+            cancelled: "Cancelled.".to_owned(),
+            timed_out: "No response, cancelled.".to_owned(),
+        }
+    }
+}
+
+impl Default for ConfirmMessages {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+/// What a handler needs to ask its user for confirmation.
+#[derive(Debug)]
+pub struct ConfirmContext<'a, Sender> {
+    pub collectors: &'a Collectors,
+    pub user_id: Id<UserMarker>,
+    pub sender: &'a Sender,
+    pub messages: ConfirmMessages,
+}
+
+impl<'a, Sender: MessageSender> ConfirmContext<'a, Sender> {
+    /// Creates a context for the author of `request`, using the collectors inserted by the
+    /// [`CollectorLayer`](crate::collector::CollectorLayer).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request has no collectors or no author.
+    pub fn from_request<CommandModel>(
+        request: &'a CommandRequest<CommandModel>,
+        sender: &'a Sender,
+    ) -> Result<Self, ConfirmError<Sender::Error>> {
+        Ok(ConfirmContext {
+            collectors: request
+                .extension::<Collectors>()
+                .ok_or(ConfirmError::MissingCollectors)?,
+            user_id: request
+                .interaction
+                .author_id()
+                .ok_or(ConfirmError::MissingAuthor)?,
+            sender,
+            messages: ConfirmMessages::default(),
+        })
+    }
+
+    #[must_use]
+    pub fn with_messages(mut self, messages: ConfirmMessages) -> Self {
+        self.messages = messages;
+        self
+    }
+}
+
+/// Asks the user to confirm `prompt` with an ephemeral message with Confirm and Cancel buttons.
+///
+/// The prompt is sent as the initial response to the interaction, so the interaction must not have
+/// been responded to or deferred yet, and anything sent afterwards has to be a follow-up. The
+/// button click reaches this dialog through the [`Collectors`], and the message is edited to
+/// show the outcome with the buttons removed.
+///
+/// # Errors
+///
+/// Returns an error if sending or editing the message fails.
+pub async fn confirm<Sender: MessageSender>(
+    ctx: &ConfirmContext<'_, Sender>,
+    prompt: impl Into<String>,
+    timeout: Duration,
+) -> Result<Confirmation, ConfirmError<Sender::Error>> {
+    let id = generate_id();
+    let button = |action: &str, label: &str, style| {
+        Component::Button(Button {
+            custom_id: Some(format!(
+                "{CONFIRM_ROUTE}{CUSTOM_ID_SEPARATOR}{id}{CUSTOM_ID_SEPARATOR}{action}"
+            )),
+            disabled: false,
+            emoji: None,
+            label: Some(label.to_owned()),
+            style,
+            url: None,
+            sku_id: None,
+        })
+    };
+
+    let filter = ComponentFilter::new()
+        .user_id(ctx.user_id)
+        .custom_id_prefix(format!(
+            "{CONFIRM_ROUTE}{CUSTOM_ID_SEPARATOR}{id}{CUSTOM_ID_SEPARATOR}"
+        ));
+
+    // Register the collector before sending, so a fast click can't arrive before it exists
+    let collected = ctx.collectors.wait_for(filter, timeout);
+
+    ctx.sender
+        .respond(InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(InteractionResponseData {
+                content: Some(prompt.into()),
+                components: Some(vec![Component::ActionRow(ActionRow {
+                    components: vec![
+                        button(CONFIRM_ACTION, &ctx.messages.confirm, ButtonStyle::Danger),
+                        button(CANCEL_ACTION, &ctx.messages.cancel, ButtonStyle::Secondary),
+                    ],
+                })]),
+                flags: Some(MessageFlags::EPHEMERAL),
+                ..InteractionResponseData::default()
+            }),
+        })
+        .await
+        .map_err(ConfirmError::Send)?;
+
+    let collected = match collected.await {
+        Ok(collected) => collected,
+        Err(CollectorError::TimedOut) => {
+            ctx.sender
+                .edit_response(resolved_data(&ctx.messages.timed_out))
+                .await
+                .map_err(ConfirmError::Send)?;
+
+            return Ok(Confirmation::TimedOut);
+        }
+    };
+
+    let (confirmation, content) = match custom_id(&collected.interaction)
+        .and_then(|custom_id| custom_id.rsplit_once(CUSTOM_ID_SEPARATOR))
+    {
+        Some((_, CONFIRM_ACTION)) => (Confirmation::Confirmed, &ctx.messages.confirmed),
+        _ => (Confirmation::Cancelled, &ctx.messages.cancelled),
+    };
+
+    // Responding to the click edits the message, so no separate edit is needed
+    collected.respond(InteractionResponse {
+        kind: InteractionResponseType::UpdateMessage,
+        data: Some(resolved_data(content)),
+    });
+
+    Ok(confirmation)
+}
+
+fn resolved_data(content: &str) -> InteractionResponseData {
+    InteractionResponseData {
+        content: Some(content.to_owned()),
+        components: Some(vec![]),
+        ..InteractionResponseData::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::collector::{CollectorLayer, CollectorOutcome, Collectors};
+    use crate::command_model_layer::CommandRequest;
+    use crate::confirm::{confirm, ConfirmContext, Confirmation, MessageSender};
+    use crate::routing::command_router::CommandRouterService;
+    use crate::routing::command_service::command_service;
+    use crate::test_utils::{self, Empty};
+    use crate::BoxFuture;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tower::{Layer, ServiceExt};
+    use twilight_model::application::interaction::Interaction;
+    use twilight_model::channel::message::Component;
+    use twilight_model::http::interaction::{
+        InteractionResponse, InteractionResponseData, InteractionResponseType,
+    };
+    use twilight_model::id::Id;
+
+    struct TestSender {
+        sent: mpsc::UnboundedSender<InteractionResponse>,
+        edits: Mutex<Vec<InteractionResponseData>>,
+    }
+
+    impl MessageSender for TestSender {
+        type Error = ();
+
+        fn respond(&self, response: InteractionResponse) -> BoxFuture<'_, Result<(), ()>> {
+            self.sent.send(response).unwrap();
+            Box::pin(std::future::ready(Ok(())))
+        }
+
+        fn edit_response(&self, data: InteractionResponseData) -> BoxFuture<'_, Result<(), ()>> {
+            self.edits.lock().unwrap().push(data);
+            Box::pin(std::future::ready(Ok(())))
+        }
+    }
+
+    #[tokio::test]
+    async fn confirms() {
+        let (sent, mut received) = mpsc::unbounded_channel();
+        let sender = Arc::new(TestSender {
+            sent,
+            edits: Mutex::new(vec![]),
+        });
+
+        let handler_sender = Arc::clone(&sender);
+        let command = move |timeout: Duration, request: CommandRequest<Empty>| {
+            let sender = Arc::clone(&handler_sender);
+            async move {
+                let ctx = ConfirmContext::from_request(&request, &*sender).map_err(|_| ())?;
+                confirm(&ctx, "Sure?", timeout).await.map_err(|_| ())
+            }
+        };
+
+        let router = CommandRouterService::new(Duration::from_secs(5))
+            .route(Id::new(1), command_service(command.clone()))
+            .route(
+                Id::new(2),
+                command_service(move |_state: Duration, request| {
+                    command(Duration::from_millis(1), request)
+                }),
+            );
+        let service = CollectorLayer::new(Collectors::new()).layer(router.interaction_router());
+
+        let command = |id| Interaction {
+            user: Some(test_utils::user(5)),
+            ..test_utils::interaction(Id::new(id))
+        };
+
+        let (command_res, click_res) = tokio::join!(service.clone().oneshot(command(1)), async {
+            let prompt = received.recv().await.unwrap();
+            assert_eq!(
+                prompt.kind,
+                InteractionResponseType::ChannelMessageWithSource
+            );
+            let Component::ActionRow(row) = &prompt.data.unwrap().components.unwrap()[0] else {
+                panic!("expected action row");
+            };
+            let Component::Button(confirm_button) = &row.components[0] else {
+                panic!("expected button");
+            };
+
+            service
+                .clone()
+                .oneshot(test_utils::click(
+                    confirm_button.custom_id.as_ref().unwrap(),
+                    5,
+                ))
+                .await
+        });
+
+        assert_eq!(
+            command_res,
+            Ok(CollectorOutcome::Routed(Some(Confirmation::Confirmed)))
+        );
+        let Ok(CollectorOutcome::Collected(response)) = click_res else {
+            panic!("click was not collected");
+        };
+        assert_eq!(response.kind, InteractionResponseType::UpdateMessage);
+        assert!(sender.edits.lock().unwrap().is_empty());
+
+        let res = service.oneshot(command(2)).await;
+        assert_eq!(
+            res,
+            Ok(CollectorOutcome::Routed(Some(Confirmation::TimedOut)))
+        );
+        assert_eq!(sender.edits.lock().unwrap().len(), 1);
+    }
+}
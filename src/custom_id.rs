@@ -0,0 +1,303 @@
+use crate::request::AsInteraction;
+use crate::routing::component_router::{custom_id, split_custom_id, CUSTOM_ID_SEPARATOR};
+use crate::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::marker::PhantomData;
+use std::task::{Context, Poll};
+use tower::{Layer, Service, ServiceExt};
+
+/// Maximum length of a component custom id allowed by Discord.
+pub const CUSTOM_ID_MAX_LENGTH: usize = 100;
+
+#[derive(Clone, Eq, PartialEq, Debug, thiserror::Error)]
+pub enum CustomIdError {
+    #[error("Interaction has no custom id")]
+    Missing,
+    #[error("Route {0} contains the custom id separator")]
+    InvalidRoute(&'static str),
+    #[error(
+        "Custom id is {length} characters long, at most {max} are allowed",
+        max = CUSTOM_ID_MAX_LENGTH
+    )]
+    TooLong { length: usize },
+    #[error("Custom id belongs to route {found}, expected {expected}")]
+    WrongRoute {
+        found: String,
+        expected: &'static str,
+    },
+    #[error("Custom id has version {found}, expected {expected}")]
+    VersionMismatch { found: u32, expected: u32 },
+    #[error("Malformed custom id payload: {0}")]
+    Malformed(String),
+}
+
+/// Data encoded into component custom ids as `route:version:payload`.
+///
+/// The payload is the value serialized as compact JSON. Tuple structs serialize without field
+/// names, so they leave the most room within the 100 character limit.
+pub trait CustomId: Serialize + DeserializeOwned {
+    /// Route name of the component, the part of the custom id the component router routes by.
+    ///
+    /// Must not contain [`CUSTOM_ID_SEPARATOR`].
+    const ROUTE: &'static str;
+    /// Version of the payload format.
+    ///
+    /// Bump this when changing the payload, custom ids with other versions fail to decode.
+    const VERSION: u32 = 1;
+
+    /// # Errors
+    ///
+    /// Returns an error if the route is invalid, the value can't be serialized or the custom id
+    /// would be too long.
+    fn to_custom_id(&self) -> Result<String, CustomIdError> {
+        if Self::ROUTE.contains(CUSTOM_ID_SEPARATOR) {
+            return Err(CustomIdError::InvalidRoute(Self::ROUTE));
+        }
+
+        let payload = serde_json::to_string(self)
+            .map_err(|error| CustomIdError::Malformed(error.to_string()))?;
+        let custom_id = format!(
+            "{route}{CUSTOM_ID_SEPARATOR}{version}{CUSTOM_ID_SEPARATOR}{payload}",
+            route = Self::ROUTE,
+            version = Self::VERSION,
+        );
+
+        let length = custom_id.chars().count();
+        if length > CUSTOM_ID_MAX_LENGTH {
+            return Err(CustomIdError::TooLong { length });
+        }
+
+        Ok(custom_id)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if `custom_id` is too long, belongs to another route or version, or its
+    /// payload is malformed.
+    fn from_custom_id(custom_id: &str) -> Result<Self, CustomIdError> {
+        let length = custom_id.chars().count();
+        if length > CUSTOM_ID_MAX_LENGTH {
+            return Err(CustomIdError::TooLong { length });
+        }
+
+        let (route, rest) = split_custom_id(custom_id);
+        if route != Self::ROUTE {
+            return Err(CustomIdError::WrongRoute {
+                found: route.to_owned(),
+                expected: Self::ROUTE,
+            });
+        }
+
+        let (version, payload) = rest
+            .and_then(|rest| rest.split_once(CUSTOM_ID_SEPARATOR))
+            .ok_or_else(|| CustomIdError::Malformed("missing version".to_owned()))?;
+        let version = version
+            .parse()
+            .map_err(|_| CustomIdError::Malformed(format!("invalid version {version}")))?;
+        if version != Self::VERSION {
+            return Err(CustomIdError::VersionMismatch {
+                found: version,
+                expected: Self::VERSION,
+            });
+        }
+
+        serde_json::from_str(payload).map_err(|error| CustomIdError::Malformed(error.to_string()))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CustomIdServiceError<ServiceError> {
+    #[error("Error decoding custom id")]
+    CustomId(#[from] CustomIdError),
+    #[error("Inner service error")]
+    Service(ServiceError),
+}
+
+/// Decodes the custom id of component interactions and passes it to the inner service.
+#[derive(Debug)]
+pub struct CustomIdLayer<T> {
+    phantom_data: PhantomData<fn() -> T>,
+}
+
+// Manually implement derive traits because T should have no bearing on the implementations
+impl<T> Clone for CustomIdLayer<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for CustomIdLayer<T> {}
+
+impl<T> Default for CustomIdLayer<T> {
+    fn default() -> Self {
+        CustomIdLayer {
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<T> CustomIdLayer<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T, TService> Layer<TService> for CustomIdLayer<T> {
+    type Service = CustomIdService<TService, T>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        CustomIdService {
+            inner,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CustomIdService<Service, T> {
+    inner: Service,
+    phantom_data: PhantomData<fn() -> T>,
+}
+
+// Manually implement Clone because T should have no bearing on the implementation
+impl<Service: Clone, T> Clone for CustomIdService<Service, T> {
+    fn clone(&self) -> Self {
+        CustomIdService {
+            inner: self.inner.clone(),
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<TService, T, Request> Service<Request> for CustomIdService<TService, T>
+where
+    TService: Service<(T, Request)> + Clone + Send + 'static,
+    TService::Response: Send + 'static,
+    TService::Error: Send + 'static,
+    TService::Future: Send,
+    T: CustomId + Send + 'static,
+    Request: AsInteraction + Send + 'static,
+{
+    type Response = TService::Response;
+    type Error = CustomIdServiceError<TService::Error>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // The inner service is driven to readiness in the returned future, after the custom id
+        // was decoded
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let decoded = custom_id(req.interaction())
+            .ok_or(CustomIdError::Missing)
+            .and_then(T::from_custom_id);
+
+        let decoded = match decoded {
+            Ok(decoded) => decoded,
+            Err(error) => return Box::pin(std::future::ready(Err(error.into()))),
+        };
+
+        let inner = self.inner.clone();
+
+        Box::pin(async move {
+            inner
+                .oneshot((decoded, req))
+                .await
+                .map_err(CustomIdServiceError::Service)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::custom_id::{CustomId, CustomIdError, CustomIdServiceError};
+    use crate::routing::component_router::ComponentRouterService;
+    use crate::test_utils;
+    use serde::{Deserialize, Serialize};
+    use tower::{service_fn, ServiceExt};
+    use twilight_model::application::interaction::Interaction;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Vote(u64, bool);
+
+    impl CustomId for Vote {
+        const ROUTE: &'static str = "vote";
+        const VERSION: u32 = 2;
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct Long(String);
+
+    impl CustomId for Long {
+        const ROUTE: &'static str = "long";
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct Separated;
+
+    impl CustomId for Separated {
+        const ROUTE: &'static str = "separated:route";
+    }
+
+    #[tokio::test]
+    async fn decodes_custom_ids() {
+        let custom_id = Vote(12, true).to_custom_id().unwrap();
+        assert_eq!(custom_id, "vote:2:[12,true]");
+
+        let router = ComponentRouterService::new().route_custom_id(service_fn(
+            |(vote, _interaction): (Vote, Interaction)| async move { Ok::<_, ()>(vote) },
+        ));
+
+        let res = router
+            .clone()
+            .oneshot(test_utils::component_interaction(&custom_id))
+            .await;
+        assert_eq!(res.unwrap(), Some(Vote(12, true)));
+
+        let res = router
+            .clone()
+            .oneshot(test_utils::component_interaction("vote:1:[12,true]"))
+            .await;
+        assert!(matches!(
+            res,
+            Err(CustomIdServiceError::CustomId(
+                CustomIdError::VersionMismatch {
+                    found: 1,
+                    expected: 2
+                }
+            ))
+        ));
+
+        let res = router
+            .oneshot(test_utils::component_interaction("vote:2:[12]"))
+            .await;
+        assert!(matches!(
+            res,
+            Err(CustomIdServiceError::CustomId(CustomIdError::Malformed(_)))
+        ));
+
+        assert_eq!(
+            Long("x".repeat(100)).to_custom_id(),
+            Err(CustomIdError::TooLong { length: 109 })
+        );
+        assert_eq!(
+            CustomIdError::TooLong { length: 109 }.to_string(),
+            "Custom id is 109 characters long, at most 100 are allowed"
+        );
+        assert_eq!(
+            Separated.to_custom_id(),
+            Err(CustomIdError::InvalidRoute("separated:route"))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "contains the custom id separator")]
+    fn rejects_routes_containing_the_separator() {
+        let _ = ComponentRouterService::new().route_custom_id(service_fn(
+            |(_, _): (Separated, Interaction)| async move { Ok::<_, ()>(()) },
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::request::AsInteraction;
+use crate::BoxFuture;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::{Layer, Service};
+use twilight_model::id::marker::InteractionMarker;
+use twilight_model::id::Id;
+
+/// Remembers which interactions were already handled.
+pub trait DedupeStore {
+    /// Records `id` as seen, returning whether it had been seen before.
+    fn check_and_insert(&self, id: Id<InteractionMarker>) -> bool;
+}
+
+/// In-memory [`DedupeStore`] remembering at most `capacity` ids for at most `ttl`.
+#[derive(Debug)]
+pub struct MemoryDedupeStore {
+    capacity: usize,
+    ttl: Duration,
+    seen: Mutex<SeenIds>,
+}
+
+#[derive(Default, Debug)]
+struct SeenIds {
+    ids: HashMap<Id<InteractionMarker>, Instant>,
+    order: VecDeque<Id<InteractionMarker>>,
+}
+
+impl MemoryDedupeStore {
+    #[must_use]
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        MemoryDedupeStore {
+            capacity,
+            ttl,
+            seen: Mutex::default(),
+        }
+    }
+}
+
+impl DedupeStore for MemoryDedupeStore {
+    fn check_and_insert(&self, id: Id<InteractionMarker>) -> bool {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if seen
+            .ids
+            .get(&id)
+            .is_some_and(|&inserted| now.duration_since(inserted) < self.ttl)
+        {
+            return true;
+        }
+
+        if self.capacity == 0 {
+            return false;
+        }
+
+        while let Some(&oldest) = seen.order.front() {
+            let expired = seen
+                .ids
+                .get(&oldest)
+                .is_none_or(|&inserted| now.duration_since(inserted) >= self.ttl);
+
+            if !expired && seen.order.len() < self.capacity {
+                break;
+            }
+
+            seen.order.pop_front();
+            seen.ids.remove(&oldest);
+        }
+
+        seen.ids.insert(id, now);
+        seen.order.push_back(id);
+
+        false
+    }
+}
+
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+pub enum DedupeOutcome<Response> {
+    Handled(Response),
+    /// The interaction was already seen and was not passed to the inner service.
+    Duplicate,
+}
+
+#[derive(Debug)]
+pub struct DedupeLayer<Store> {
+    store: Arc<Store>,
+}
+
+impl<Store> Clone for DedupeLayer<Store> {
+    fn clone(&self) -> Self {
+        DedupeLayer {
+            store: Arc::clone(&self.store),
+        }
+    }
+}
+
+impl<Store> DedupeLayer<Store> {
+    #[must_use]
+    pub fn new(store: Store) -> Self {
+        DedupeLayer {
+            store: Arc::new(store),
+        }
+    }
+}
+
+impl<TService, Store> Layer<TService> for DedupeLayer<Store> {
+    type Service = DedupeService<TService, Store>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        DedupeService {
+            inner,
+            store: Arc::clone(&self.store),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DedupeService<Service, Store> {
+    inner: Service,
+    store: Arc<Store>,
+}
+
+impl<Service: Clone, Store> Clone for DedupeService<Service, Store> {
+    fn clone(&self) -> Self {
+        DedupeService {
+            inner: self.inner.clone(),
+            store: Arc::clone(&self.store),
+        }
+    }
+}
+
+impl<TService, Store, Request> Service<Request> for DedupeService<TService, Store>
+where
+    TService: Service<Request>,
+    TService::Response: Send + 'static,
+    TService::Error: Send + 'static,
+    TService::Future: Send + 'static,
+    Store: DedupeStore,
+    Request: AsInteraction,
+{
+    type Response = DedupeOutcome<TService::Response>;
+    type Error = TService::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if self.store.check_and_insert(req.interaction().id) {
+            return Box::pin(std::future::ready(Ok(DedupeOutcome::Duplicate)));
+        }
+
+        let future = self.inner.call(req);
+
+        Box::pin(async move { future.await.map(DedupeOutcome::Handled) })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dedupe::{DedupeLayer, DedupeOutcome, DedupeStore, MemoryDedupeStore};
+    use crate::routing::InteractionRouterService;
+    use crate::test_utils;
+    use std::time::Duration;
+    use tower::{service_fn, Layer, ServiceExt};
+    use twilight_model::application::interaction::Interaction;
+    use twilight_model::id::Id;
+
+    #[tokio::test]
+    async fn skips_duplicates() {
+        let router = InteractionRouterService::new().route(
+            Id::new(1),
+            service_fn(|_: Interaction| async { Ok::<_, ()>(1) }),
+        );
+        let service =
+            DedupeLayer::new(MemoryDedupeStore::new(16, Duration::from_secs(5))).layer(router);
+
+        let res = service
+            .clone()
+            .oneshot(test_utils::interaction(Id::new(1)))
+            .await;
+        assert_eq!(res, Ok(DedupeOutcome::Handled(Some(1))));
+
+        let res = service.oneshot(test_utils::interaction(Id::new(1))).await;
+        assert_eq!(res, Ok(DedupeOutcome::Duplicate));
+    }
+
+    #[test]
+    fn memory_store_is_bounded() {
+        let store = MemoryDedupeStore::new(2, Duration::from_secs(5));
+
+        assert!(!store.check_and_insert(Id::new(1)));
+        assert!(!store.check_and_insert(Id::new(2)));
+        assert!(store.check_and_insert(Id::new(2)));
+        assert!(!store.check_and_insert(Id::new(3)));
+        assert!(!store.check_and_insert(Id::new(1)));
+    }
+
+    #[test]
+    fn memory_store_detects_oldest_at_capacity() {
+        let store = MemoryDedupeStore::new(2, Duration::from_secs(5));
+
+        assert!(!store.check_and_insert(Id::new(1)));
+        assert!(!store.check_and_insert(Id::new(2)));
+        assert!(store.check_and_insert(Id::new(1)));
+        assert!(store.check_and_insert(Id::new(2)));
+    }
+}
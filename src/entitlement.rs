@@ -0,0 +1,229 @@
+use crate::error_renderer::ephemeral_response;
+use crate::request::AsInteraction;
+use crate::BoxFuture;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::SystemTime;
+use tower::{Layer, Service};
+use twilight_model::application::interaction::Interaction;
+use twilight_model::application::monetization::Entitlement;
+use twilight_model::channel::message::component::{ActionRow, Button, ButtonStyle};
+use twilight_model::channel::message::Component;
+use twilight_model::http::interaction::{InteractionResponse, InteractionResponseType};
+use twilight_model::id::marker::SkuMarker;
+use twilight_model::id::Id;
+
+/// Discord allows at most five buttons per action row.
+const BUTTONS_PER_ROW: usize = 5;
+
+/// Returns whether `entitlement` is currently active.
+#[must_use]
+pub fn is_active(entitlement: &Entitlement) -> bool {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| {
+            i64::try_from(elapsed.as_micros()).unwrap_or(i64::MAX)
+        });
+
+    !entitlement.deleted
+        && entitlement
+            .starts_at
+            .is_none_or(|starts_at| starts_at.as_micros() <= now)
+        && entitlement
+            .ends_at
+            .is_none_or(|ends_at| ends_at.as_micros() > now)
+}
+
+/// Returns whether `interaction` carries an active entitlement to any of `skus`.
+#[must_use]
+pub fn has_entitlement(interaction: &Interaction, skus: &[Id<SkuMarker>]) -> bool {
+    interaction
+        .entitlements
+        .iter()
+        .any(|entitlement| skus.contains(&entitlement.sku_id) && is_active(entitlement))
+}
+
+/// How to respond to interactions missing a required entitlement.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum MissingEntitlementResponse {
+    /// Respond with [`InteractionResponseType::PremiumRequired`].
+    ///
+    /// Discord deprecated this response type in favor of premium buttons.
+    PremiumRequired,
+    /// Respond with an ephemeral message with a premium button for each required SKU.
+    Upsell(String),
+}
+
+/// Creates the response for an interaction missing an entitlement to any of `skus`.
+#[must_use]
+pub fn missing_entitlement_response(
+    skus: &[Id<SkuMarker>],
+    response: &MissingEntitlementResponse,
+) -> InteractionResponse {
+    match response {
+        #[allow(deprecated)]
+        MissingEntitlementResponse::PremiumRequired => InteractionResponse {
+            kind: InteractionResponseType::PremiumRequired,
+            data: None,
+        },
+        MissingEntitlementResponse::Upsell(message) => {
+            let mut response = ephemeral_response(message.clone());
+            if let Some(data) = &mut response.data {
+                data.components = Some(
+                    skus.chunks(BUTTONS_PER_ROW)
+                        .map(|skus| {
+                            Component::ActionRow(ActionRow {
+                                components: skus.iter().map(|&sku| premium_button(sku)).collect(),
+                            })
+                        })
+                        .collect(),
+                );
+            }
+
+            response
+        }
+    }
+}
+
+fn premium_button(sku_id: Id<SkuMarker>) -> Component {
+    Component::Button(Button {
+        custom_id: None,
+        disabled: false,
+        emoji: None,
+        label: None,
+        style: ButtonStyle::Premium,
+        url: None,
+        sku_id: Some(sku_id),
+    })
+}
+
+/// Only lets interactions with an active entitlement to one of the configured SKUs through.
+///
+/// Other interactions are answered with the configured [`MissingEntitlementResponse`] without
+/// calling the inner service.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct RequireEntitlementLayer {
+    skus: Arc<[Id<SkuMarker>]>,
+    response: Arc<MissingEntitlementResponse>,
+}
+
+impl RequireEntitlementLayer {
+    #[must_use]
+    pub fn new(skus: impl IntoIterator<Item = Id<SkuMarker>>) -> Self {
+        RequireEntitlementLayer {
+            skus: skus.into_iter().collect(),
+            response: Arc::new(MissingEntitlementResponse::Upsell(
+                "This command requires a premium subscription.".to_owned(),
+            )),
+        }
+    }
+
+    #[must_use]
+    pub fn with_response(mut self, response: MissingEntitlementResponse) -> Self {
+        self.response = Arc::new(response);
+        self
+    }
+}
+
+impl<TService> Layer<TService> for RequireEntitlementLayer {
+    type Service = RequireEntitlementService<TService>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        RequireEntitlementService {
+            inner,
+            skus: Arc::clone(&self.skus),
+            response: Arc::clone(&self.response),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RequireEntitlementService<Service> {
+    inner: Service,
+    skus: Arc<[Id<SkuMarker>]>,
+    response: Arc<MissingEntitlementResponse>,
+}
+
+impl<TService, Request> Service<Request> for RequireEntitlementService<TService>
+where
+    TService: Service<Request>,
+    TService::Response: From<InteractionResponse> + Send + 'static,
+    TService::Error: Send + 'static,
+    TService::Future: Send + 'static,
+    Request: AsInteraction,
+{
+    type Response = TService::Response;
+    type Error = TService::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if has_entitlement(req.interaction(), &self.skus) {
+            Box::pin(self.inner.call(req))
+        } else {
+            let response = missing_entitlement_response(&self.skus, &self.response);
+            Box::pin(std::future::ready(Ok(response.into())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::entitlement::{MissingEntitlementResponse, RequireEntitlementLayer};
+    use crate::error_renderer::ephemeral_response;
+    use crate::test_utils;
+    use tower::{service_fn, Layer, ServiceExt};
+    use twilight_model::application::interaction::Interaction;
+    use twilight_model::application::monetization::Entitlement;
+    use twilight_model::http::interaction::{InteractionResponse, InteractionResponseType};
+    use twilight_model::id::Id;
+
+    fn entitlement(sku_id: u64, deleted: bool) -> Entitlement {
+        serde_json::from_value(serde_json::json!({
+            "application_id": "1",
+            "deleted": deleted,
+            "id": "1",
+            "type": 8,
+            "sku_id": sku_id.to_string(),
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn requires_entitlement() {
+        let service = RequireEntitlementLayer::new([Id::new(10)])
+            .with_response(MissingEntitlementResponse::Upsell("Buy premium".to_owned()))
+            .layer(service_fn(|_: Interaction| async {
+                Ok::<_, ()>(ephemeral_response("ok".to_owned()))
+            }));
+
+        let mut interaction = test_utils::interaction(Id::new(1));
+        interaction.entitlements = vec![entitlement(10, false)];
+        let res = service.clone().oneshot(interaction).await.unwrap();
+        assert_eq!(res, ephemeral_response("ok".to_owned()));
+
+        let mut interaction = test_utils::interaction(Id::new(1));
+        interaction.entitlements = vec![entitlement(10, true), entitlement(11, false)];
+        let res = service.oneshot(interaction).await.unwrap();
+        let data = res.data.unwrap();
+        assert_eq!(data.content.as_deref(), Some("Buy premium"));
+        assert_eq!(data.components.unwrap().len(), 1);
+
+        #[allow(deprecated)]
+        let premium_required = InteractionResponse {
+            kind: InteractionResponseType::PremiumRequired,
+            data: None,
+        };
+        let res = RequireEntitlementLayer::new([Id::new(10)])
+            .with_response(MissingEntitlementResponse::PremiumRequired)
+            .layer(service_fn(|_: Interaction| async {
+                Ok::<_, ()>(ephemeral_response("ok".to_owned()))
+            }))
+            .oneshot(test_utils::interaction(Id::new(1)))
+            .await;
+        assert_eq!(res, Ok(premium_required));
+    }
+}
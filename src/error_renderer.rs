@@ -0,0 +1,352 @@
+use crate::catch_panic::CatchPanicError;
+use crate::command_model_layer::CommandModelServiceError;
+use crate::guard::GuardError;
+use crate::request::AsInteraction;
+use crate::BoxFuture;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use twilight_interactions::error::{ParseError, ParseOptionErrorType};
+use twilight_model::channel::message::MessageFlags;
+use twilight_model::http::interaction::{
+    InteractionResponse, InteractionResponseData, InteractionResponseType,
+};
+
+/// Turns errors into responses shown to the user who triggered the interaction.
+pub trait ErrorRenderer<Error> {
+    /// Renders `error` for a user with the given locale.
+    ///
+    /// Returning `None` passes the error on instead of responding.
+    fn render(&self, error: &Error, locale: Option<&str>) -> Option<InteractionResponse>;
+}
+
+/// Errors that can be described to users with [`ErrorMessages`].
+pub trait DescribeError {
+    fn describe(&self, messages: &ErrorMessages) -> String;
+}
+
+impl<ServiceError> DescribeError for CommandModelServiceError<ServiceError> {
+    fn describe(&self, messages: &ErrorMessages) -> String {
+        match self {
+            CommandModelServiceError::Parse(ParseError::EmptyOptions) => {
+                messages.empty_options.clone()
+            }
+            CommandModelServiceError::Parse(ParseError::Option(error)) => messages
+                .parse_option
+                .replace("{field}", &error.field)
+                .replace("{kind}", messages.parse_kind(&error.kind)),
+            CommandModelServiceError::NotACommand => messages.not_a_command.clone(),
+            CommandModelServiceError::Service(_) => messages.internal.clone(),
+            CommandModelServiceError::Timeout => messages.timeout.clone(),
+            CommandModelServiceError::RateLimited => messages.rate_limited.clone(),
+            CommandModelServiceError::WrongInstallation => messages.wrong_installation.clone(),
+            CommandModelServiceError::Unresolved(unresolved) => messages
+                .parse_option
+                .replace("{field}", &unresolved.field)
+                .replace("{kind}", &messages.lookup_failed),
+            CommandModelServiceError::Invalid(invalid) => messages.invalid_options.replace(
+                "{errors}",
+                &invalid
+                    .0
+                    .iter()
+                    .map(|error| format!("- {error}"))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+        }
+    }
+}
+
+impl<ServiceError: DescribeError> DescribeError for GuardError<ServiceError> {
+    fn describe(&self, messages: &ErrorMessages) -> String {
+        match self {
+            GuardError::Rejected(rejection) => messages
+                .guard_rejected
+                .replace("{reason}", &rejection.to_string()),
+            GuardError::Service(error) => error.describe(messages),
+        }
+    }
+}
+
+impl<ServiceError: DescribeError> DescribeError for CatchPanicError<ServiceError> {
+    fn describe(&self, messages: &ErrorMessages) -> String {
+        match self {
+            CatchPanicError::Panicked(_) => messages.internal.clone(),
+            CatchPanicError::Service(error) => error.describe(messages),
+        }
+    }
+}
+
+/// Message templates used by [`DefaultErrorRenderer`].
+///
+/// `parse_option` may contain `{field}` and `{kind}` placeholders, `guard_rejected` may contain a
+/// `{reason}` placeholder and `invalid_options` may contain an `{errors}` placeholder, replaced by
+/// one line per failed check.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ErrorMessages {
+    pub parse_option: String,
+    pub empty_options: String,
+    pub invalid_type: String,
+    pub invalid_choice: String,
+    pub out_of_range: String,
+    pub invalid_channel_type: String,
+    pub lookup_failed: String,
+    pub required_field: String,
+    pub unknown_field: String,
+    pub unknown_subcommand: String,
+    pub not_a_command: String,
+    pub guard_rejected: String,
+    pub timeout: String,
+    pub rate_limited: String,
+    pub wrong_installation: String,
+    pub invalid_options: String,
+    pub internal: String,
+}
+
+impl ErrorMessages {
+    #[must_use]
+    pub fn english() -> Self {
+        ErrorMessages {
+            parse_option: "Invalid option `{field}`: {kind}.".to_owned(),
+            empty_options: "This command requires options.".to_owned(),
+            invalid_type: "the value has the wrong type".to_owned(),
+            invalid_choice: "the value is not one of the choices".to_owned(),
+            out_of_range: "the value is out of range".to_owned(),
+            invalid_channel_type: "this kind of channel is not allowed".to_owned(),
+            lookup_failed: "the value could not be found".to_owned(),
+            required_field: "the option is required".to_owned(),
+            unknown_field: "the option is unknown".to_owned(),
+            unknown_subcommand: "the subcommand is unknown".to_owned(),
+            not_a_command: "This interaction is not a command.".to_owned(),
+            guard_rejected: "You can't use this command: {reason}".to_owned(),
+            timeout: "This command took too long to respond.".to_owned(),
+            rate_limited: "This command is used too often, please try again later.".to_owned(),
+            wrong_installation: "This command is not available here.".to_owned(),
+            invalid_options: "Invalid options:\n{errors}".to_owned(),
+            internal: "Something went wrong while running this command.".to_owned(),
+        }
+    }
+
+    #[must_use]
+    pub fn parse_kind(&self, kind: &ParseOptionErrorType) -> &str {
+        match kind {
+            ParseOptionErrorType::InvalidType(_) => &self.invalid_type,
+            ParseOptionErrorType::InvalidChoice(_) => &self.invalid_choice,
+            ParseOptionErrorType::IntegerOutOfRange(_)
+            | ParseOptionErrorType::NumberOutOfRange(_)
+            | ParseOptionErrorType::StringLengthOutOfRange(_) => &self.out_of_range,
+            ParseOptionErrorType::InvalidChannelType(_) => &self.invalid_channel_type,
+            ParseOptionErrorType::LookupFailed(_) => &self.lookup_failed,
+            ParseOptionErrorType::RequiredField => &self.required_field,
+            ParseOptionErrorType::UnknownField => &self.unknown_field,
+            ParseOptionErrorType::UnknownSubcommand => &self.unknown_subcommand,
+        }
+    }
+}
+
+impl Default for ErrorMessages {
+    fn default() -> Self {
+        ErrorMessages::english()
+    }
+}
+
+/// Renders [`DescribeError`]s as ephemeral messages, using per-locale [`ErrorMessages`].
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct DefaultErrorRenderer {
+    fallback: ErrorMessages,
+    locales: HashMap<String, ErrorMessages>,
+}
+
+impl DefaultErrorRenderer {
+    #[must_use]
+    pub fn new(fallback: ErrorMessages) -> Self {
+        DefaultErrorRenderer {
+            fallback,
+            locales: HashMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_locale(mut self, locale: impl Into<String>, messages: ErrorMessages) -> Self {
+        self.locales.insert(locale.into(), messages);
+        self
+    }
+
+    #[must_use]
+    pub fn messages(&self, locale: Option<&str>) -> &ErrorMessages {
+        locale
+            .and_then(|locale| self.locales.get(locale))
+            .unwrap_or(&self.fallback)
+    }
+}
+
+impl<Error: DescribeError> ErrorRenderer<Error> for DefaultErrorRenderer {
+    fn render(&self, error: &Error, locale: Option<&str>) -> Option<InteractionResponse> {
+        Some(ephemeral_response(error.describe(self.messages(locale))))
+    }
+}
+
+#[must_use]
+pub fn ephemeral_response(content: String) -> InteractionResponse {
+    InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(InteractionResponseData {
+            content: Some(content),
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..InteractionResponseData::default()
+        }),
+    }
+}
+
+#[derive(Debug)]
+pub struct RenderErrorLayer<Renderer> {
+    renderer: Arc<Renderer>,
+}
+
+impl<Renderer> Clone for RenderErrorLayer<Renderer> {
+    fn clone(&self) -> Self {
+        RenderErrorLayer {
+            renderer: Arc::clone(&self.renderer),
+        }
+    }
+}
+
+impl<Renderer> RenderErrorLayer<Renderer> {
+    #[must_use]
+    pub fn new(renderer: Renderer) -> Self {
+        RenderErrorLayer {
+            renderer: Arc::new(renderer),
+        }
+    }
+}
+
+impl<TService, Renderer> Layer<TService> for RenderErrorLayer<Renderer> {
+    type Service = RenderErrorService<TService, Renderer>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        RenderErrorService {
+            inner,
+            renderer: Arc::clone(&self.renderer),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RenderErrorService<Service, Renderer> {
+    inner: Service,
+    renderer: Arc<Renderer>,
+}
+
+impl<Service: Clone, Renderer> Clone for RenderErrorService<Service, Renderer> {
+    fn clone(&self) -> Self {
+        RenderErrorService {
+            inner: self.inner.clone(),
+            renderer: Arc::clone(&self.renderer),
+        }
+    }
+}
+
+impl<TService, Renderer, Request> Service<Request> for RenderErrorService<TService, Renderer>
+where
+    TService: Service<Request>,
+    TService::Response: From<InteractionResponse> + Send + 'static,
+    TService::Error: Send + 'static,
+    TService::Future: Send + 'static,
+    Renderer: ErrorRenderer<TService::Error> + Send + Sync + 'static,
+    Request: AsInteraction,
+{
+    type Response = TService::Response;
+    type Error = TService::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let interaction = req.interaction();
+        let locale = interaction
+            .locale
+            .clone()
+            .or_else(|| interaction.guild_locale.clone());
+        let renderer = Arc::clone(&self.renderer);
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            match future.await {
+                Ok(response) => Ok(response),
+                Err(error) => match renderer.render(&error, locale.as_deref()) {
+                    Some(response) => Ok(response.into()),
+                    None => Err(error),
+                },
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error_renderer::{
+        ephemeral_response, DefaultErrorRenderer, ErrorMessages, RenderErrorLayer,
+    };
+    use crate::guard::{GuardLayer, GuardRejection};
+    use crate::routing::command_router::CommandRouterService;
+    use crate::routing::command_service::command_service;
+    use crate::test_utils;
+    use tower::ServiceExt;
+    use twilight_interactions::command::CommandModel;
+    use twilight_model::application::interaction::Interaction;
+    use twilight_model::http::interaction::InteractionResponse;
+    use twilight_model::id::Id;
+
+    #[derive(CommandModel)]
+    struct WithOption {
+        _value: i64,
+    }
+
+    #[tokio::test]
+    async fn renders_errors() {
+        async fn command(_state: (), _model: WithOption) -> Result<InteractionResponse, ()> {
+            Ok(ephemeral_response("ok".to_owned()))
+        }
+
+        let german = ErrorMessages {
+            parse_option: "Ungültige Option `{field}`: {kind}.".to_owned(),
+            required_field: "die Option wird benötigt".to_owned(),
+            ..ErrorMessages::english()
+        };
+
+        let router = CommandRouterService::new(())
+            .route(Id::new(1), command_service(command))
+            .route(Id::new(2), command_service(command))
+            .layer(GuardLayer::new(|interaction: &Interaction| {
+                if interaction.id == Id::new(2) {
+                    Err(GuardRejection::Custom("not today".to_owned()))
+                } else {
+                    Ok(())
+                }
+            }))
+            .layer(RenderErrorLayer::new(
+                DefaultErrorRenderer::default().with_locale("de", german),
+            ));
+
+        let mut interaction = test_utils::interaction(Id::new(1));
+        interaction.locale = Some("de".to_owned());
+        let res = router.clone().oneshot(interaction).await.unwrap().unwrap();
+        assert_eq!(
+            res,
+            ephemeral_response("Ungültige Option `_value`: die Option wird benötigt.".to_owned())
+        );
+
+        let res = router
+            .oneshot(test_utils::interaction(Id::new(2)))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            res,
+            ephemeral_response("You can't use this command: not today".to_owned())
+        );
+    }
+}
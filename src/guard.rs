@@ -0,0 +1,96 @@
+use crate::request::AsInteraction;
+use crate::BoxFuture;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use twilight_model::application::interaction::Interaction;
+
+/// Reason a [`Guard`] rejected an interaction.
+#[derive(Clone, Eq, PartialEq, Debug, thiserror::Error)]
+pub enum GuardRejection {
+    #[error("{0}")]
+    Custom(String),
+    #[error("not available here")]
+    WrongInstallation,
+}
+
+#[derive(Clone, PartialEq, Debug, thiserror::Error)]
+pub enum GuardError<ServiceError> {
+    #[error("Guard rejected interaction: {0}")]
+    Rejected(GuardRejection),
+    #[error("Inner service error")]
+    Service(ServiceError),
+}
+
+/// Decides whether an interaction may reach the guarded service.
+pub trait Guard {
+    /// # Errors
+    ///
+    /// Returns the reason for rejecting the interaction if it should not reach the service.
+    fn check(&self, interaction: &Interaction) -> Result<(), GuardRejection>;
+}
+
+impl<F> Guard for F
+where
+    F: Fn(&Interaction) -> Result<(), GuardRejection>,
+{
+    fn check(&self, interaction: &Interaction) -> Result<(), GuardRejection> {
+        self(interaction)
+    }
+}
+
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+pub struct GuardLayer<Guard> {
+    guard: Guard,
+}
+
+impl<Guard> GuardLayer<Guard> {
+    #[must_use]
+    pub fn new(guard: Guard) -> Self {
+        GuardLayer { guard }
+    }
+}
+
+impl<TService, TGuard: Clone> Layer<TService> for GuardLayer<TGuard> {
+    type Service = GuardService<TService, TGuard>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        GuardService {
+            inner,
+            guard: self.guard.clone(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+pub struct GuardService<Service, Guard> {
+    inner: Service,
+    guard: Guard,
+}
+
+impl<TService, TGuard, Request> Service<Request> for GuardService<TService, TGuard>
+where
+    TService: Service<Request>,
+    TService::Response: Send + 'static,
+    TService::Error: Send + 'static,
+    TService::Future: Send + 'static,
+    TGuard: Guard,
+    Request: AsInteraction,
+{
+    type Response = TService::Response;
+    type Error = GuardError<TService::Error>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(GuardError::Service)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if let Err(rejection) = self.guard.check(req.interaction()) {
+            return Box::pin(std::future::ready(Err(GuardError::Rejected(rejection))));
+        }
+
+        let future = self.inner.call(req);
+
+        Box::pin(async move { future.await.map_err(GuardError::Service) })
+    }
+}
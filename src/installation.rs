@@ -0,0 +1,154 @@
+use crate::guard::{Guard, GuardRejection};
+use twilight_model::application::command::Command;
+use twilight_model::application::interaction::{Interaction, InteractionContextType};
+use twilight_model::oauth::ApplicationIntegrationType;
+
+/// Returns the installation types that authorized `interaction`.
+#[must_use]
+pub fn integration_types(interaction: &Interaction) -> Vec<ApplicationIntegrationType> {
+    let owners = &interaction.authorizing_integration_owners;
+
+    let guild = owners
+        .guild
+        .as_ref()
+        .map(|_| ApplicationIntegrationType::GuildInstall);
+    let user = owners
+        .user
+        .as_ref()
+        .map(|_| ApplicationIntegrationType::UserInstall);
+
+    guild.into_iter().chain(user).collect()
+}
+
+/// Restricts where and through which installations an interaction may be used.
+///
+/// `None` allows everything, mirroring the `contexts` and `integration_types` fields of commands.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct InstallationFilter {
+    pub contexts: Option<Vec<InteractionContextType>>,
+    pub integration_types: Option<Vec<ApplicationIntegrationType>>,
+}
+
+impl InstallationFilter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn contexts(mut self, contexts: impl IntoIterator<Item = InteractionContextType>) -> Self {
+        self.contexts = Some(contexts.into_iter().collect());
+        self
+    }
+
+    #[must_use]
+    pub fn integration_types(
+        mut self,
+        integration_types: impl IntoIterator<Item = ApplicationIntegrationType>,
+    ) -> Self {
+        self.integration_types = Some(integration_types.into_iter().collect());
+        self
+    }
+
+    /// Returns whether `interaction` was used in an allowed context through an allowed
+    /// installation.
+    ///
+    /// Interactions without a context are only allowed if contexts are unrestricted.
+    #[must_use]
+    pub fn allows(&self, interaction: &Interaction) -> bool {
+        let context_allowed = self.contexts.as_ref().is_none_or(|contexts| {
+            interaction
+                .context
+                .is_some_and(|context| contexts.contains(&context))
+        });
+
+        let integration_type_allowed =
+            self.integration_types.as_ref().is_none_or(|allowed_types| {
+                integration_types(interaction)
+                    .iter()
+                    .any(|integration_type| allowed_types.contains(integration_type))
+            });
+
+        context_allowed && integration_type_allowed
+    }
+
+    /// Sets the `contexts` and `integration_types` of `command` to match this filter.
+    pub fn apply_to(&self, command: &mut Command) {
+        command.contexts.clone_from(&self.contexts);
+        command
+            .integration_types
+            .clone_from(&self.integration_types);
+    }
+}
+
+impl Guard for InstallationFilter {
+    fn check(&self, interaction: &Interaction) -> Result<(), GuardRejection> {
+        if self.allows(interaction) {
+            Ok(())
+        } else {
+            Err(GuardRejection::WrongInstallation)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command_model_layer::CommandModelServiceError;
+    use crate::guard::{Guard, GuardRejection};
+    use crate::installation::InstallationFilter;
+    use crate::routing::command_router::CommandRouterService;
+    use crate::routing::command_service::command_service;
+    use crate::routing::route_options::RouteOptions;
+    use crate::test_utils::{self, Empty};
+    use tower::ServiceExt;
+    use twilight_interactions::command::CreateCommand;
+    use twilight_model::application::interaction::InteractionContextType;
+    use twilight_model::id::Id;
+    use twilight_model::oauth::ApplicationIntegrationType;
+
+    #[tokio::test]
+    async fn filters_installations() {
+        async fn command(_state: (), _model: Empty) -> Result<i64, ()> {
+            Ok(1)
+        }
+
+        let filter = InstallationFilter::new()
+            .contexts([
+                InteractionContextType::BotDm,
+                InteractionContextType::PrivateChannel,
+            ])
+            .integration_types([ApplicationIntegrationType::UserInstall]);
+
+        let router = CommandRouterService::new(()).route_with(
+            Id::new(1),
+            command_service(command),
+            RouteOptions {
+                installation: Some(filter.clone()),
+                ..RouteOptions::default()
+            },
+        );
+
+        let mut interaction = test_utils::interaction(Id::new(1));
+        interaction.context = Some(InteractionContextType::PrivateChannel);
+        interaction.authorizing_integration_owners.user = Some(Id::new(5));
+        assert_eq!(filter.check(&interaction), Ok(()));
+        let res = router.clone().oneshot(interaction.clone()).await;
+        assert_eq!(res, Ok(Some(1)));
+
+        interaction.context = Some(InteractionContextType::Guild);
+        assert_eq!(
+            filter.check(&interaction),
+            Err(GuardRejection::WrongInstallation)
+        );
+        let res = router.clone().oneshot(interaction).await;
+        assert_eq!(res, Err(CommandModelServiceError::WrongInstallation));
+
+        let (_, metadata) = router.routes().next().unwrap();
+        let mut command = Empty::create_command().into();
+        metadata.installation.unwrap().apply_to(&mut command);
+        assert_eq!(
+            command.integration_types,
+            Some(vec![ApplicationIntegrationType::UserInstall])
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use crate::error_renderer::ephemeral_response;
+use crate::request::AsInteraction;
+use crate::routing::route::RouteMetadata;
+use crate::BoxFuture;
+use arc_swap::ArcSwap;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use twilight_model::application::interaction::{Interaction, InteractionData, InteractionType};
+use twilight_model::http::interaction::{
+    InteractionResponse, InteractionResponseData, InteractionResponseType,
+};
+use twilight_model::id::marker::GuildMarker;
+use twilight_model::id::Id;
+
+#[derive(Clone, Default, Debug)]
+struct Disabled {
+    all: bool,
+    commands: BTreeSet<String>,
+    guilds: BTreeSet<Id<GuildMarker>>,
+}
+
+/// Set of disabled commands and guilds, shared between clones and updatable at runtime.
+#[derive(Clone, Default, Debug)]
+pub struct KillSwitch {
+    disabled: Arc<ArcSwap<Disabled>>,
+}
+
+impl KillSwitch {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn update(&self, f: impl Fn(&mut Disabled)) {
+        self.disabled.rcu(|disabled| {
+            let mut disabled = Disabled::clone(disabled);
+            f(&mut disabled);
+            disabled
+        });
+    }
+
+    /// Disables every interaction, for example during maintenance.
+    pub fn disable_all(&self) {
+        self.update(|disabled| disabled.all = true);
+    }
+
+    pub fn enable_all(&self) {
+        self.update(|disabled| disabled.all = false);
+    }
+
+    /// Disables the top-level command called `name`.
+    pub fn disable_command(&self, name: impl Into<String>) {
+        let name = name.into();
+        self.update(|disabled| {
+            disabled.commands.insert(name.clone());
+        });
+    }
+
+    pub fn enable_command(&self, name: &str) {
+        self.update(|disabled| {
+            disabled.commands.remove(name);
+        });
+    }
+
+    pub fn disable_guild(&self, guild_id: Id<GuildMarker>) {
+        self.update(|disabled| {
+            disabled.guilds.insert(guild_id);
+        });
+    }
+
+    pub fn enable_guild(&self, guild_id: Id<GuildMarker>) {
+        self.update(|disabled| {
+            disabled.guilds.remove(&guild_id);
+        });
+    }
+
+    #[must_use]
+    pub fn is_all_disabled(&self) -> bool {
+        self.disabled.load().all
+    }
+
+    #[must_use]
+    pub fn disabled_commands(&self) -> Vec<String> {
+        self.disabled.load().commands.iter().cloned().collect()
+    }
+
+    #[must_use]
+    pub fn disabled_guilds(&self) -> Vec<Id<GuildMarker>> {
+        self.disabled.load().guilds.iter().copied().collect()
+    }
+
+    /// Returns whether `interaction` should be rejected.
+    ///
+    /// Pings are never disabled, so Discord can still verify the interactions endpoint.
+    #[must_use]
+    pub fn is_disabled(&self, interaction: &Interaction) -> bool {
+        if interaction.kind == InteractionType::Ping {
+            return false;
+        }
+
+        let disabled = self.disabled.load();
+
+        disabled.all
+            || interaction
+                .guild_id
+                .is_some_and(|guild_id| disabled.guilds.contains(&guild_id))
+            || matches!(
+                &interaction.data,
+                Some(InteractionData::ApplicationCommand(data))
+                    if disabled.commands.contains(&data.name)
+            )
+    }
+
+    /// Lists what is disabled, including which of `routes` are disabled.
+    pub fn status<Key>(
+        &self,
+        routes: impl IntoIterator<Item = (Key, RouteMetadata)>,
+    ) -> KillSwitchStatus<Key> {
+        let disabled = self.disabled.load();
+
+        let routes = routes
+            .into_iter()
+            .filter(|(_, metadata)| {
+                disabled.all
+                    || metadata
+                        .name
+                        .as_ref()
+                        .is_some_and(|name| disabled.commands.contains(name))
+            })
+            .collect();
+
+        KillSwitchStatus {
+            all: disabled.all,
+            commands: disabled.commands.iter().cloned().collect(),
+            guilds: disabled.guilds.iter().copied().collect(),
+            routes,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct KillSwitchStatus<Key> {
+    pub all: bool,
+    pub commands: Vec<String>,
+    pub guilds: Vec<Id<GuildMarker>>,
+    /// Routes disabled by name, or all routes if everything is disabled.
+    pub routes: Vec<(Key, RouteMetadata)>,
+}
+
+/// Answers commands and components disabled by a [`KillSwitch`] with an ephemeral maintenance
+/// message instead of calling the inner service.
+///
+/// Disabled autocomplete interactions get no choices, other interactions are passed through.
+#[derive(Clone, Debug)]
+pub struct KillSwitchLayer {
+    kill_switch: KillSwitch,
+    message: Arc<str>,
+}
+
+impl KillSwitchLayer {
+    #[must_use]
+    pub fn new(kill_switch: KillSwitch) -> Self {
+        KillSwitchLayer {
+            kill_switch,
+            message: "This command is temporarily disabled for maintenance.".into(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into().into();
+        self
+    }
+}
+
+impl<TService> Layer<TService> for KillSwitchLayer {
+    type Service = KillSwitchService<TService>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        KillSwitchService {
+            inner,
+            kill_switch: self.kill_switch.clone(),
+            message: Arc::clone(&self.message),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KillSwitchService<Service> {
+    inner: Service,
+    kill_switch: KillSwitch,
+    message: Arc<str>,
+}
+
+impl<TService, Request> Service<Request> for KillSwitchService<TService>
+where
+    TService: Service<Request>,
+    TService::Response: From<InteractionResponse> + Send + 'static,
+    TService::Error: Send + 'static,
+    TService::Future: Send + 'static,
+    Request: AsInteraction,
+{
+    type Response = TService::Response;
+    type Error = TService::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let interaction = req.interaction();
+        if !self.kill_switch.is_disabled(interaction) {
+            return Box::pin(self.inner.call(req));
+        }
+
+        let response = match interaction.kind {
+            InteractionType::ApplicationCommand | InteractionType::MessageComponent => {
+                ephemeral_response(self.message.to_string())
+            }
+            InteractionType::ApplicationCommandAutocomplete => InteractionResponse {
+                kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+                data: Some(InteractionResponseData {
+                    choices: Some(vec![]),
+                    ..InteractionResponseData::default()
+                }),
+            },
+            _ => return Box::pin(self.inner.call(req)),
+        };
+
+        Box::pin(std::future::ready(Ok(response.into())))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error_renderer::ephemeral_response;
+    use crate::kill_switch::{KillSwitch, KillSwitchLayer};
+    use crate::routing::route::RouteMetadata;
+    use crate::test_utils;
+    use tower::{service_fn, Layer, ServiceExt};
+    use twilight_model::application::interaction::{Interaction, InteractionType};
+    use twilight_model::http::interaction::InteractionResponseType;
+    use twilight_model::id::Id;
+
+    #[tokio::test]
+    async fn kill_switch() {
+        let kill_switch = KillSwitch::new();
+        let service = KillSwitchLayer::new(kill_switch.clone())
+            .with_message("Down for maintenance")
+            .layer(service_fn(|_: Interaction| async {
+                Ok::<_, ()>(ephemeral_response("ok".to_owned()))
+            }));
+
+        let interaction = Interaction {
+            guild_id: Some(Id::new(2)),
+            ..test_utils::command_interaction("ban", &[])
+        };
+
+        let ok = ephemeral_response("ok".to_owned());
+        let down = ephemeral_response("Down for maintenance".to_owned());
+        let call = |interaction: &Interaction| service.clone().oneshot(interaction.clone());
+
+        assert_eq!(call(&interaction).await, Ok(ok.clone()));
+
+        kill_switch.disable_command("ban");
+        assert_eq!(call(&interaction).await, Ok(down.clone()));
+        kill_switch.enable_command("ban");
+
+        kill_switch.disable_guild(Id::new(2));
+        assert_eq!(call(&interaction).await, Ok(down.clone()));
+        kill_switch.enable_guild(Id::new(2));
+
+        kill_switch.disable_all();
+        assert_eq!(call(&interaction).await, Ok(down));
+        kill_switch.enable_all();
+
+        assert_eq!(call(&interaction).await, Ok(ok.clone()));
+
+        kill_switch.disable_command("kick");
+        let routes = [
+            (
+                1,
+                RouteMetadata {
+                    name: Some("ban".to_owned()),
+                    ..RouteMetadata::default()
+                },
+            ),
+            (
+                2,
+                RouteMetadata {
+                    name: Some("kick".to_owned()),
+                    ..RouteMetadata::default()
+                },
+            ),
+        ];
+        kill_switch.disable_guild(Id::new(3));
+        let status = kill_switch.status(routes);
+        assert_eq!(status.routes.len(), 1);
+        assert_eq!(status.routes[0].0, 2);
+        assert_eq!(status.commands, ["kick"]);
+        assert_eq!(status.guilds, [Id::new(3)]);
+
+        kill_switch.disable_all();
+        let ping = Interaction {
+            kind: InteractionType::Ping,
+            data: None,
+            ..test_utils::interaction(Id::new(1))
+        };
+        assert_eq!(call(&ping).await, Ok(ok.clone()));
+        let autocomplete = Interaction {
+            kind: InteractionType::ApplicationCommandAutocomplete,
+            ..test_utils::interaction(Id::new(1))
+        };
+        let res = call(&autocomplete).await.unwrap();
+        assert_eq!(
+            res.kind,
+            InteractionResponseType::ApplicationCommandAutocompleteResult
+        );
+    }
+}
@@ -1,19 +1,56 @@
 #![forbid(unsafe_code)]
 #![warn(clippy::pedantic)]
 
+pub mod audit;
+pub mod catch_panic;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod collector;
 pub mod command_model_layer;
+pub mod confirm;
+pub mod custom_id;
+pub mod dedupe;
+pub mod entitlement;
+pub mod error_renderer;
+pub mod guard;
+pub mod installation;
+pub mod kill_switch;
+mod line_writer;
+pub mod localization;
+pub mod option_validation;
+pub mod paginator;
+pub mod record;
+pub mod request;
+pub mod resolved;
+pub mod rollout;
 pub mod routing;
+pub mod session;
 pub mod state;
+pub mod validate;
+
+use std::future::Future;
+use std::pin::Pin;
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 
 #[cfg(test)]
 mod test_utils {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use twilight_interactions::command::{CommandModel, CreateCommand};
     use twilight_model::application::command::CommandType;
-    use twilight_model::application::interaction::application_command::CommandData;
+    use twilight_model::application::interaction::application_command::{
+        CommandData, CommandDataOption, CommandOptionValue,
+    };
+    use twilight_model::application::interaction::message_component::MessageComponentInteractionData;
     use twilight_model::application::interaction::{Interaction, InteractionData, InteractionType};
+    use twilight_model::channel::message::component::ComponentType;
     use twilight_model::id::marker::InteractionMarker;
     use twilight_model::id::Id;
     use twilight_model::oauth::ApplicationIntegrationMap;
+    use twilight_model::user::User;
 
+    #[allow(deprecated)]
     pub fn interaction(id: Id<InteractionMarker>) -> Interaction {
         Interaction {
             app_permissions: None,
@@ -47,6 +84,79 @@ mod test_utils {
             user: None,
         }
     }
+
+    /// An invocation of the command `name` with `options`.
+    pub fn command_interaction(name: &str, options: &[(&str, CommandOptionValue)]) -> Interaction {
+        let mut interaction = interaction(Id::new(1));
+        if let Some(InteractionData::ApplicationCommand(data)) = &mut interaction.data {
+            data.name = name.to_owned();
+            data.options = options
+                .iter()
+                .map(|(name, value)| CommandDataOption {
+                    name: (*name).to_owned(),
+                    value: value.clone(),
+                })
+                .collect();
+        }
+        interaction
+    }
+
+    #[derive(CommandModel, CreateCommand)]
+    #[command(name = "empty", desc = "Takes no options")]
+    pub struct Empty {}
+
+    /// Writer whose contents can be read while it is owned elsewhere.
+    #[derive(Clone, Default, Debug)]
+    pub struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuffer {
+        pub fn contents(&self) -> Vec<u8> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    pub fn user(id: u64) -> User {
+        serde_json::from_value(serde_json::json!({
+            "id": id.to_string(),
+            "username": "user",
+            "discriminator": "0",
+            "avatar": null,
+        }))
+        .unwrap()
+    }
+
+    /// A button click by the user `user_id`.
+    pub fn click(custom_id: &str, user_id: u64) -> Interaction {
+        Interaction {
+            user: Some(user(user_id)),
+            ..component_interaction(custom_id)
+        }
+    }
+
+    pub fn component_interaction(custom_id: &str) -> Interaction {
+        Interaction {
+            data: Some(InteractionData::MessageComponent(Box::new(
+                MessageComponentInteractionData {
+                    custom_id: custom_id.to_owned(),
+                    component_type: ComponentType::Button,
+                    resolved: None,
+                    values: vec![],
+                },
+            ))),
+            kind: InteractionType::MessageComponent,
+            ..interaction(Id::new(1))
+        }
+    }
 }
 
 #[cfg(test)]
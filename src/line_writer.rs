@@ -0,0 +1,53 @@
+use std::io::Write;
+use std::sync::mpsc;
+use std::thread;
+use tokio::sync::oneshot;
+
+enum Message {
+    Line(Vec<u8>),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Writes lines on a dedicated thread, so writing never blocks the async runtime.
+///
+/// Write errors are ignored. The thread exits once the writer is dropped.
+#[derive(Debug)]
+pub(crate) struct LineWriter {
+    sender: mpsc::Sender<Message>,
+}
+
+impl LineWriter {
+    pub(crate) fn new(mut writer: impl Write + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for message in receiver {
+                match message {
+                    Message::Line(line) => {
+                        let _ = writer.write_all(&line).and_then(|()| writer.flush());
+                    }
+                    Message::Flush(done) => {
+                        let _ = writer.flush();
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+
+        LineWriter { sender }
+    }
+
+    pub(crate) fn write_line(&self, mut line: Vec<u8>) {
+        line.push(b'\n');
+        let _ = self.sender.send(Message::Line(line));
+    }
+
+    /// Waits until all lines written so far reached the writer.
+    pub(crate) async fn flush(&self) {
+        let (done, receiver) = oneshot::channel();
+
+        if self.sender.send(Message::Flush(done)).is_ok() {
+            let _ = receiver.await;
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::localization::Localizer;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::path::Path;
+use unic_langid::LanguageIdentifier;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FluentLocalizerError {
+    #[error("Invalid locale {0}")]
+    InvalidLocale(String),
+    #[error("Error reading fluent resource")]
+    Io(#[from] std::io::Error),
+    #[error("Error parsing fluent resource: {0}")]
+    Parse(String),
+}
+
+/// A [`Localizer`] backed by [Fluent](https://projectfluent.org) resources, one bundle per locale.
+///
+/// Placeables are not wrapped in Unicode isolation marks, because Discord renders them literally.
+#[derive(Default)]
+pub struct FluentLocalizer {
+    bundles: HashMap<String, FluentBundle<FluentResource>>,
+}
+
+impl Debug for FluentLocalizer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FluentLocalizer")
+            .field("locales", &self.bundles.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl FluentLocalizer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the Fluent source `source` to the bundle of `locale`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `locale` is not a valid language identifier or `source` is invalid.
+    pub fn add_resource(
+        &mut self,
+        locale: &str,
+        source: String,
+    ) -> Result<(), FluentLocalizerError> {
+        let resource =
+            FluentResource::try_new(source).map_err(|(_, errors)| parse_error(&errors))?;
+
+        let bundle = match self.bundles.entry(locale.to_owned()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let language: LanguageIdentifier = locale
+                    .parse()
+                    .map_err(|_| FluentLocalizerError::InvalidLocale(locale.to_owned()))?;
+                let mut bundle = FluentBundle::new_concurrent(vec![language]);
+                bundle.set_use_isolating(false);
+                entry.insert(bundle)
+            }
+        };
+
+        bundle
+            .add_resource(resource)
+            .map_err(|errors| parse_error(&errors))
+    }
+
+    /// Loads every `<locale>.ftl` file in `directory`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading the directory or adding any of the resources fails.
+    pub fn from_directory(directory: impl AsRef<Path>) -> Result<Self, FluentLocalizerError> {
+        let mut localizer = FluentLocalizer::new();
+
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "ftl") {
+                continue;
+            }
+            let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            localizer.add_resource(locale, std::fs::read_to_string(&path)?)?;
+        }
+
+        Ok(localizer)
+    }
+}
+
+fn parse_error(errors: &[impl Display]) -> FluentLocalizerError {
+    FluentLocalizerError::Parse(
+        errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+impl Localizer for FluentLocalizer {
+    fn localize(&self, locale: &str, key: &str, args: &[(&str, &str)]) -> Option<String> {
+        let bundle = self.bundles.get(locale)?;
+        let pattern = bundle.get_message(key)?.value()?;
+
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, *value);
+        }
+
+        let mut errors = vec![];
+        let message = bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+
+        Some(message.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::localization::fluent::FluentLocalizer;
+    use crate::localization::Localizer;
+
+    #[test]
+    fn localizes() {
+        let mut localizer = FluentLocalizer::new();
+        localizer
+            .add_resource(
+                "de",
+                "greeting = Hallo { $name }!\nerror-timeout = Zu langsam.".to_owned(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            localizer.localize("de", "greeting", &[("name", "Welt")]),
+            Some("Hallo Welt!".to_owned())
+        );
+        assert_eq!(localizer.localize("de", "missing", &[]), None);
+        assert_eq!(localizer.localize("fr", "greeting", &[]), None);
+        assert!(localizer.add_resource("de", "= broken".to_owned()).is_err());
+    }
+}
@@ -0,0 +1,471 @@
+#[cfg(feature = "fluent")]
+pub mod fluent;
+
+use crate::error_renderer::{ephemeral_response, DescribeError, ErrorMessages, ErrorRenderer};
+use crate::request::InteractionRequest;
+use arc_swap::ArcSwap;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use twilight_model::application::command::{Command, CommandOption};
+use twilight_model::application::interaction::Interaction;
+use twilight_model::http::interaction::InteractionResponse;
+
+/// Looks up translated messages by locale and key.
+pub trait Localizer {
+    /// Returns the message for `key` in `locale` with `args` filled in, or `None` if there is no
+    /// such message.
+    fn localize(&self, locale: &str, key: &str, args: &[(&str, &str)]) -> Option<String>;
+}
+
+impl<T: Localizer + ?Sized> Localizer for Arc<T> {
+    fn localize(&self, locale: &str, key: &str, args: &[(&str, &str)]) -> Option<String> {
+        T::localize(self, locale, key, args)
+    }
+}
+
+/// A [`Localizer`] backed by in-memory templates with `{name}` placeholders for arguments.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct MemoryLocalizer {
+    messages: HashMap<String, HashMap<String, String>>,
+}
+
+impl MemoryLocalizer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_message(
+        mut self,
+        locale: impl Into<String>,
+        key: impl Into<String>,
+        template: impl Into<String>,
+    ) -> Self {
+        self.messages
+            .entry(locale.into())
+            .or_default()
+            .insert(key.into(), template.into());
+        self
+    }
+}
+
+impl Localizer for MemoryLocalizer {
+    fn localize(&self, locale: &str, key: &str, args: &[(&str, &str)]) -> Option<String> {
+        let template = self.messages.get(locale)?.get(key)?;
+
+        Some(
+            args.iter()
+                .fold(template.clone(), |message, (name, value)| {
+                    message.replace(&format!("{{{name}}}"), value)
+                }),
+        )
+    }
+}
+
+/// Returns the locale of the user who triggered `interaction`, falling back to the guild locale.
+#[must_use]
+pub fn interaction_locale(interaction: &Interaction) -> Option<&str> {
+    interaction
+        .locale
+        .as_deref()
+        .or(interaction.guild_locale.as_deref())
+}
+
+/// A [`Localizer`] bound to the locale of an interaction.
+///
+/// Inserted into the request extensions by the [`LocalizationLayer`].
+#[derive(Clone)]
+pub struct Translator {
+    localizer: Arc<dyn Localizer + Send + Sync>,
+    locale: Option<String>,
+    fallback_locale: Arc<str>,
+}
+
+impl Debug for Translator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Translator")
+            .field("locale", &self.locale)
+            .field("fallback_locale", &self.fallback_locale)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Translator {
+    #[must_use]
+    pub fn new(
+        localizer: Arc<dyn Localizer + Send + Sync>,
+        locale: Option<String>,
+        fallback_locale: &str,
+    ) -> Self {
+        Translator {
+            localizer,
+            locale,
+            fallback_locale: fallback_locale.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    /// Translates `key`, returning the key itself if neither the bound nor the fallback locale
+    /// have a message for it.
+    #[must_use]
+    pub fn translate(&self, key: &str) -> String {
+        self.translate_with(key, &[])
+    }
+
+    #[must_use]
+    pub fn translate_with(&self, key: &str, args: &[(&str, &str)]) -> String {
+        self.try_translate(key, args)
+            .unwrap_or_else(|| key.to_owned())
+    }
+
+    #[must_use]
+    pub fn try_translate(&self, key: &str, args: &[(&str, &str)]) -> Option<String> {
+        self.locale
+            .as_deref()
+            .and_then(|locale| self.localizer.localize(locale, key, args))
+            .or_else(|| self.localizer.localize(&self.fallback_locale, key, args))
+    }
+}
+
+#[derive(Clone)]
+pub struct LocalizationLayer {
+    localizer: Arc<dyn Localizer + Send + Sync>,
+    fallback_locale: Arc<str>,
+}
+
+impl Debug for LocalizationLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalizationLayer")
+            .field("fallback_locale", &self.fallback_locale)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LocalizationLayer {
+    #[must_use]
+    pub fn new(localizer: impl Localizer + Send + Sync + 'static, fallback_locale: &str) -> Self {
+        LocalizationLayer {
+            localizer: Arc::new(localizer),
+            fallback_locale: fallback_locale.into(),
+        }
+    }
+}
+
+impl<TService> Layer<TService> for LocalizationLayer {
+    type Service = LocalizationService<TService>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        LocalizationService {
+            inner,
+            localizer: Arc::clone(&self.localizer),
+            fallback_locale: Arc::clone(&self.fallback_locale),
+        }
+    }
+}
+
+/// Inserts a [`Translator`] for the interaction's locale into the request extensions.
+#[derive(Clone)]
+pub struct LocalizationService<Service> {
+    inner: Service,
+    localizer: Arc<dyn Localizer + Send + Sync>,
+    fallback_locale: Arc<str>,
+}
+
+impl<Service: Debug> Debug for LocalizationService<Service> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalizationService")
+            .field("inner", &self.inner)
+            .field("fallback_locale", &self.fallback_locale)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<TService, Request> Service<Request> for LocalizationService<TService>
+where
+    TService: Service<InteractionRequest>,
+    Request: Into<InteractionRequest>,
+{
+    type Response = TService::Response;
+    type Error = TService::Error;
+    type Future = TService::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let mut req = req.into();
+
+        let translator = Translator {
+            localizer: Arc::clone(&self.localizer),
+            locale: interaction_locale(&req.interaction).map(ToOwned::to_owned),
+            fallback_locale: Arc::clone(&self.fallback_locale),
+        };
+        req.extensions.insert(translator);
+
+        self.inner.call(req)
+    }
+}
+
+/// Renders [`DescribeError`]s with [`ErrorMessages`] looked up from a [`Localizer`].
+///
+/// Each message is looked up by its field name in kebab case, prefixed with `error-`, e.g.
+/// `error-parse-option`. Placeholders are passed as the arguments `field`, `kind`, `reason` and
+/// `errors`.
+/// Messages missing for a locale are taken from the fallback messages. The messages of each locale
+/// are looked up once and shared between clones.
+#[derive(Clone, Debug)]
+pub struct LocalizedErrorRenderer<L> {
+    localizer: L,
+    fallback: Arc<ErrorMessages>,
+    cache: Arc<ArcSwap<HashMap<String, Arc<ErrorMessages>>>>,
+}
+
+impl<L: Localizer> LocalizedErrorRenderer<L> {
+    #[must_use]
+    pub fn new(localizer: L, fallback: ErrorMessages) -> Self {
+        LocalizedErrorRenderer {
+            localizer,
+            fallback: Arc::new(fallback),
+            cache: Arc::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn messages(&self, locale: Option<&str>) -> Arc<ErrorMessages> {
+        let Some(locale) = locale else {
+            return Arc::clone(&self.fallback);
+        };
+
+        if let Some(messages) = self.cache.load().get(locale) {
+            return Arc::clone(messages);
+        }
+
+        let messages = Arc::new(self.localize_messages(locale));
+        self.cache.rcu(|cache| {
+            let mut cache = HashMap::clone(cache);
+            cache
+                .entry(locale.to_owned())
+                .or_insert_with(|| Arc::clone(&messages));
+            cache
+        });
+
+        messages
+    }
+
+    fn localize_messages(&self, locale: &str) -> ErrorMessages {
+        let args = [
+            ("field", "{field}"),
+            ("kind", "{kind}"),
+            ("reason", "{reason}"),
+            ("errors", "{errors}"),
+        ];
+        let message = |key: &str, fallback: &String| {
+            self.localizer
+                .localize(locale, &format!("error-{key}"), &args)
+                .unwrap_or_else(|| fallback.clone())
+        };
+
+        let fallback = &self.fallback;
+        ErrorMessages {
+            parse_option: message("parse-option", &fallback.parse_option),
+            empty_options: message("empty-options", &fallback.empty_options),
+            invalid_type: message("invalid-type", &fallback.invalid_type),
+            invalid_choice: message("invalid-choice", &fallback.invalid_choice),
+            out_of_range: message("out-of-range", &fallback.out_of_range),
+            invalid_channel_type: message("invalid-channel-type", &fallback.invalid_channel_type),
+            lookup_failed: message("lookup-failed", &fallback.lookup_failed),
+            required_field: message("required-field", &fallback.required_field),
+            unknown_field: message("unknown-field", &fallback.unknown_field),
+            unknown_subcommand: message("unknown-subcommand", &fallback.unknown_subcommand),
+            not_a_command: message("not-a-command", &fallback.not_a_command),
+            guard_rejected: message("guard-rejected", &fallback.guard_rejected),
+            timeout: message("timeout", &fallback.timeout),
+            rate_limited: message("rate-limited", &fallback.rate_limited),
+            wrong_installation: message("wrong-installation", &fallback.wrong_installation),
+            invalid_options: message("invalid-options", &fallback.invalid_options),
+            internal: message("internal", &fallback.internal),
+        }
+    }
+}
+
+impl<L: Localizer, Error: DescribeError> ErrorRenderer<Error> for LocalizedErrorRenderer<L> {
+    fn render(&self, error: &Error, locale: Option<&str>) -> Option<InteractionResponse> {
+        Some(ephemeral_response(error.describe(&self.messages(locale))))
+    }
+}
+
+/// Fills in `name_localizations` and `description_localizations` of `command`, its options and
+/// the names of their choices for each of `locales`.
+///
+/// Keys are the path of names joined by `-` and suffixed with `-name` or `-description`, e.g.
+/// `ban-name`, `ban-user-description` or `ban-reason-spam-name` for a choice. Missing messages are
+/// left out.
+pub fn localize_command(command: &mut Command, localizer: &impl Localizer, locales: &[&str]) {
+    let path = command.name.clone();
+
+    extend_localizations(
+        &mut command.name_localizations,
+        localizer,
+        locales,
+        &format!("{path}-name"),
+    );
+    extend_localizations(
+        &mut command.description_localizations,
+        localizer,
+        locales,
+        &format!("{path}-description"),
+    );
+
+    for option in &mut command.options {
+        localize_option(option, localizer, locales, &path);
+    }
+}
+
+fn localize_option(
+    option: &mut CommandOption,
+    localizer: &impl Localizer,
+    locales: &[&str],
+    parent_path: &str,
+) {
+    let path = format!("{parent_path}-{}", option.name);
+
+    extend_localizations(
+        &mut option.name_localizations,
+        localizer,
+        locales,
+        &format!("{path}-name"),
+    );
+    extend_localizations(
+        &mut option.description_localizations,
+        localizer,
+        locales,
+        &format!("{path}-description"),
+    );
+
+    for choice in option.choices.iter_mut().flatten() {
+        extend_localizations(
+            &mut choice.name_localizations,
+            localizer,
+            locales,
+            &format!("{path}-{}-name", choice.name),
+        );
+    }
+
+    for option in option.options.iter_mut().flatten() {
+        localize_option(option, localizer, locales, &path);
+    }
+}
+
+fn extend_localizations(
+    localizations: &mut Option<HashMap<String, String>>,
+    localizer: &impl Localizer,
+    locales: &[&str],
+    key: &str,
+) {
+    for locale in locales {
+        if let Some(message) = localizer.localize(locale, key, &[]) {
+            localizations
+                .get_or_insert_with(HashMap::new)
+                .insert((*locale).to_owned(), message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command_model_layer::{CommandModelServiceError, CommandRequest};
+    use crate::error_renderer::{ephemeral_response, ErrorMessages, ErrorRenderer};
+    use crate::localization::{
+        localize_command, LocalizationLayer, LocalizedErrorRenderer, MemoryLocalizer, Translator,
+    };
+    use crate::routing::command_router::CommandRouterService;
+    use crate::routing::command_service::command_service;
+    use crate::test_utils;
+    use std::sync::Arc;
+    use tower::{Layer, ServiceExt};
+    use twilight_interactions::command::{CommandModel, CreateCommand};
+    use twilight_model::id::Id;
+
+    #[derive(CommandModel, CreateCommand)]
+    #[command(name = "greet", desc = "Greets someone")]
+    struct Greet {
+        /// Who to greet
+        name: Option<String>,
+    }
+
+    #[tokio::test]
+    async fn localizes() {
+        async fn command(_state: (), request: CommandRequest<Greet>) -> Result<String, ()> {
+            let translator = request.extension::<Translator>().ok_or(())?;
+            let name = request.model.name.as_deref().unwrap_or("?");
+
+            Ok(translator.translate_with("greeting", &[("name", name)]))
+        }
+
+        let localizer = MemoryLocalizer::new()
+            .with_message("en-US", "greeting", "Hello {name}!")
+            .with_message("de", "greeting", "Hallo {name}!")
+            .with_message("de", "greet-name", "grüßen")
+            .with_message("de", "greet-name-description", "Wen grüßen")
+            .with_message("de", "error-timeout", "Zu langsam.");
+
+        let router = CommandRouterService::new(()).route(Id::new(1), command_service(command));
+        let service =
+            LocalizationLayer::new(localizer.clone(), "en-US").layer(router.interaction_router());
+
+        let mut interaction = test_utils::interaction(Id::new(1));
+        interaction.guild_locale = Some("de".to_owned());
+        let res = service.clone().oneshot(interaction).await;
+        assert_eq!(res.unwrap(), Some("Hallo ?!".to_owned()));
+
+        let mut interaction = test_utils::interaction(Id::new(1));
+        interaction.locale = Some("fr".to_owned());
+        let res = service.oneshot(interaction).await;
+        assert_eq!(res.unwrap(), Some("Hello ?!".to_owned()));
+
+        let renderer = LocalizedErrorRenderer::new(localizer.clone(), ErrorMessages::english());
+        let error = CommandModelServiceError::<()>::Timeout;
+        assert_eq!(
+            renderer.render(&error, Some("de")),
+            Some(ephemeral_response("Zu langsam.".to_owned()))
+        );
+        assert_eq!(
+            renderer.render(&CommandModelServiceError::<()>::NotACommand, Some("de")),
+            Some(ephemeral_response(ErrorMessages::english().not_a_command))
+        );
+        assert!(Arc::ptr_eq(
+            &renderer.messages(Some("de")),
+            &renderer.clone().messages(Some("de"))
+        ));
+
+        let mut command = Greet::create_command().into();
+        localize_command(&mut command, &localizer, &["de", "fr"]);
+        assert_eq!(
+            command
+                .name_localizations
+                .unwrap()
+                .get("de")
+                .map(String::as_str),
+            Some("grüßen")
+        );
+        assert_eq!(command.description_localizations, None);
+        assert_eq!(
+            command.options[0]
+                .description_localizations
+                .as_ref()
+                .unwrap()
+                .get("de")
+                .map(String::as_str),
+            Some("Wen grüßen")
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use crate::command_model_layer::{CommandInput, CommandRequest};
+use crate::request::InteractionRequest;
+use std::fmt::{Display, Formatter};
+use twilight_interactions::command::CommandModel;
+
+/// A validation failure, for a single option or for the command as a whole.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct FieldError {
+    /// Name of the offending option, `None` for errors involving several options.
+    pub field: Option<String>,
+    /// Message suitable for showing to the user.
+    pub message: String,
+}
+
+impl FieldError {
+    #[must_use]
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        FieldError {
+            field: Some(field.into()),
+            message: message.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn command(message: impl Into<String>) -> Self {
+        FieldError {
+            field: None,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "`{field}`: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// The errors found by [`Validate::validate`].
+#[derive(Clone, Default, Eq, PartialEq, Debug, thiserror::Error)]
+#[error("Invalid options: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+pub struct InvalidOptions(pub Vec<FieldError>);
+
+/// Semantic checks on a parsed command model.
+pub trait Validate {
+    /// # Errors
+    ///
+    /// Returns every violated constraint.
+    fn validate(&self) -> Result<(), InvalidOptions>;
+}
+
+/// Command input that is only passed to the service if [`Validate::validate`] succeeds.
+///
+/// Failures are returned as
+/// [`CommandModelServiceError::Invalid`](crate::command_model_layer::CommandModelServiceError::Invalid).
+#[derive(Clone, Debug)]
+pub struct Validated<CommandModel>(pub CommandModel);
+
+impl<TCommandModel: CommandModel + Validate> CommandInput for Validated<TCommandModel> {
+    type Model = TCommandModel;
+
+    fn from_request(
+        model: Self::Model,
+        _request: InteractionRequest,
+    ) -> Result<Self, InvalidOptions> {
+        model.validate()?;
+        Ok(Validated(model))
+    }
+}
+
+impl<TCommandModel: CommandModel + Validate> CommandInput
+    for CommandRequest<Validated<TCommandModel>>
+{
+    type Model = TCommandModel;
+
+    fn from_request(
+        model: Self::Model,
+        request: InteractionRequest,
+    ) -> Result<Self, InvalidOptions> {
+        model.validate()?;
+        Ok(CommandRequest {
+            model: Validated(model),
+            interaction: request.interaction,
+            extensions: request.extensions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command_model_layer::CommandModelServiceError;
+    use crate::error_renderer::{DescribeError, ErrorMessages};
+    use crate::option_validation::{FieldError, InvalidOptions, Validate, Validated};
+    use crate::routing::command_router::CommandRouterService;
+    use crate::routing::command_service::command_service;
+    use crate::test_utils;
+    use tower::ServiceExt;
+    use twilight_interactions::command::CommandModel;
+    use twilight_model::application::interaction::application_command::CommandOptionValue;
+    use twilight_model::id::Id;
+
+    #[derive(CommandModel)]
+    struct Link {
+        url: String,
+    }
+
+    impl Validate for Link {
+        fn validate(&self) -> Result<(), InvalidOptions> {
+            if self.url.starts_with("https://") {
+                Ok(())
+            } else {
+                Err(InvalidOptions(vec![FieldError::new(
+                    "url",
+                    "must be an https link",
+                )]))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn validates_models() {
+        async fn command(_state: (), Validated(link): Validated<Link>) -> Result<String, ()> {
+            Ok(link.url)
+        }
+
+        let router = CommandRouterService::new(()).route(Id::new(1), command_service(command));
+        let interaction = |url: &str| {
+            test_utils::command_interaction(
+                "link",
+                &[("url", CommandOptionValue::String(url.to_owned()))],
+            )
+        };
+
+        let res = router.clone().oneshot(interaction("https://a")).await;
+        assert_eq!(res, Ok(Some("https://a".to_owned())));
+
+        let res = router.oneshot(interaction("ftp://a")).await;
+        let error = res.unwrap_err();
+        assert_eq!(
+            error,
+            CommandModelServiceError::Invalid(InvalidOptions(vec![FieldError::new(
+                "url",
+                "must be an https link"
+            )]))
+        );
+        assert_eq!(
+            error.describe(&ErrorMessages::english()),
+            "Invalid options:\n- `url`: must be an https link"
+        );
+    }
+}
@@ -0,0 +1,569 @@
+use crate::error_renderer::ephemeral_response;
+use crate::request::AsInteraction;
+use crate::routing::component_router::custom_id;
+use crate::session::{session_id, Session, SessionError, SessionStore, Sessions};
+use crate::BoxFuture;
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+use tokio::sync::OwnedMutexGuard;
+use tower::Service;
+use twilight_model::channel::message::component::{ActionRow, Button, ButtonStyle};
+use twilight_model::channel::message::{Component, Embed};
+use twilight_model::http::interaction::{
+    InteractionResponse, InteractionResponseData, InteractionResponseType,
+};
+use twilight_model::id::marker::UserMarker;
+use twilight_model::id::Id;
+
+const PREVIOUS_ACTION: &str = "prev";
+const NEXT_ACTION: &str = "next";
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Page {
+    pub embed: Embed,
+    pub has_next: bool,
+}
+
+/// Provides the pages shown by a [`Paginator`].
+pub trait PageSource {
+    /// Data identifying what to page through, stored in the paginator session.
+    type Context: Serialize + DeserializeOwned + Send + Sync + 'static;
+
+    /// Returns the page at `index`, or `None` if there is no such page.
+    fn page<'a>(&'a self, context: &'a Self::Context, index: usize) -> BoxFuture<'a, Option<Page>>;
+}
+
+/// The same pages for every invocation.
+impl PageSource for Vec<Embed> {
+    type Context = ();
+
+    fn page<'a>(
+        &'a self,
+        _context: &'a Self::Context,
+        index: usize,
+    ) -> BoxFuture<'a, Option<Page>> {
+        Box::pin(std::future::ready(embed_page(self, index)))
+    }
+}
+
+/// Pages given per invocation, stored in the paginator session.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug)]
+pub struct EmbedPages;
+
+impl PageSource for EmbedPages {
+    type Context = Vec<Embed>;
+
+    fn page<'a>(&'a self, context: &'a Self::Context, index: usize) -> BoxFuture<'a, Option<Page>> {
+        Box::pin(std::future::ready(embed_page(context, index)))
+    }
+}
+
+fn embed_page(embeds: &[Embed], index: usize) -> Option<Page> {
+    embeds.get(index).map(|embed| Page {
+        embed: embed.clone(),
+        has_next: index + 1 < embeds.len(),
+    })
+}
+
+/// Edits the message showing a paginator, i.e. the response to the interaction that started it.
+pub trait ResponseEditor {
+    type Error;
+
+    fn edit_response(
+        &self,
+        data: InteractionResponseData,
+    ) -> BoxFuture<'_, Result<(), Self::Error>>;
+}
+
+/// Future returned by [`Paginator::start_with_expiry`].
+pub type Expiry<E> = BoxFuture<'static, Result<(), E>>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PaginatorError {
+    #[error("Error accessing paginator session")]
+    Session(#[from] SessionError),
+    #[error("Page source has no page {0}")]
+    PageNotFound(usize),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound(deserialize = "Context: DeserializeOwned"))]
+struct PaginatorState<Context> {
+    user_id: Id<UserMarker>,
+    page: usize,
+    context: Context,
+}
+
+/// Pages through embeds with previous/next buttons, routed to its route name on a
+/// [`ComponentRouterService`](crate::routing::component_router::ComponentRouterService).
+///
+/// Only the user who started it can change pages.
+pub struct Paginator<Source, Store> {
+    route: Arc<str>,
+    source: Arc<Source>,
+    sessions: Sessions<Store>,
+    locks: Arc<SessionLocks>,
+    ttl: Duration,
+    not_invoker_message: Arc<str>,
+}
+
+#[derive(Default)]
+struct SessionLocks(Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>);
+
+struct SessionGuard {
+    guard: Option<OwnedMutexGuard<()>>,
+    locks: Arc<SessionLocks>,
+    id: String,
+}
+
+impl SessionLocks {
+    async fn lock(self: &Arc<Self>, id: &str) -> SessionGuard {
+        let lock = {
+            let mut locks = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+            Arc::clone(locks.entry(id.to_owned()).or_default())
+        };
+
+        SessionGuard {
+            guard: Some(lock.lock_owned().await),
+            locks: Arc::clone(self),
+            id: id.to_owned(),
+        }
+    }
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        let mut locks = self.locks.0.lock().unwrap_or_else(PoisonError::into_inner);
+        self.guard = None;
+
+        if locks
+            .get(&self.id)
+            .is_some_and(|lock| Arc::strong_count(lock) == 1)
+        {
+            locks.remove(&self.id);
+        }
+    }
+}
+
+impl<Source, Store> Clone for Paginator<Source, Store> {
+    fn clone(&self) -> Self {
+        Paginator {
+            route: Arc::clone(&self.route),
+            source: Arc::clone(&self.source),
+            sessions: self.sessions.clone(),
+            locks: Arc::clone(&self.locks),
+            ttl: self.ttl,
+            not_invoker_message: Arc::clone(&self.not_invoker_message),
+        }
+    }
+}
+
+impl<Source, Store> Debug for Paginator<Source, Store> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Paginator")
+            .field("route", &self.route)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Source, Store> Paginator<Source, Store>
+where
+    Source: PageSource,
+    Store: SessionStore,
+{
+    #[must_use]
+    pub fn new(route: &str, source: Source, sessions: Sessions<Store>, ttl: Duration) -> Self {
+        Paginator {
+            route: route.into(),
+            source: Arc::new(source),
+            sessions,
+            locks: Arc::default(),
+            ttl,
+            not_invoker_message: "Only the user who ran this command can change pages.".into(),
+        }
+    }
+
+    /// Sets the ephemeral message shown when someone other than the invoking user clicks a button.
+    #[must_use]
+    pub fn with_not_invoker_message(mut self, message: &str) -> Self {
+        self.not_invoker_message = message.into();
+        self
+    }
+
+    /// Starts paginating `context` for `user_id`, returning the response showing the first page.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source has no first page or the session could not be created.
+    pub async fn start(
+        &self,
+        user_id: Id<UserMarker>,
+        context: Source::Context,
+    ) -> Result<InteractionResponse, PaginatorError> {
+        self.start_session(user_id, context)
+            .await
+            .map(|(_, response)| response)
+    }
+
+    async fn start_session(
+        &self,
+        user_id: Id<UserMarker>,
+        context: Source::Context,
+    ) -> Result<(String, InteractionResponse), PaginatorError> {
+        let page = self
+            .source
+            .page(&context, 0)
+            .await
+            .ok_or(PaginatorError::PageNotFound(0))?;
+
+        let session = self
+            .sessions
+            .create(
+                PaginatorState {
+                    user_id,
+                    page: 0,
+                    context,
+                },
+                self.ttl,
+            )
+            .await?;
+
+        let response = InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(self.page_data(&session, page)),
+        };
+
+        Ok((session.id, response))
+    }
+
+    /// Starts paginating like [`start`](Self::start), also returning a future that disables the
+    /// buttons through `editor` once the session has expired.
+    ///
+    /// The future should be spawned after responding with the returned response.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source has no first page or the session could not be created.
+    pub async fn start_with_expiry<Editor>(
+        &self,
+        user_id: Id<UserMarker>,
+        context: Source::Context,
+        editor: Editor,
+    ) -> Result<(InteractionResponse, Expiry<Editor::Error>), PaginatorError>
+    where
+        Store: Send + Sync + 'static,
+        Editor: ResponseEditor + Send + Sync + 'static,
+    {
+        let (id, response) = self.start_session(user_id, context).await?;
+        let sessions = self.sessions.clone();
+
+        let expiry = Box::pin(async move {
+            let mut expires_at = SystemTime::now();
+            // Sleep until the session can't be loaded anymore, in case its expiry was extended
+            while let Ok(Some(session)) = sessions.load::<IgnoredAny>(&id).await {
+                expires_at = expires_at.max(session.expires_at);
+                let remaining = expires_at
+                    .duration_since(SystemTime::now())
+                    .unwrap_or_default();
+                tokio::time::sleep(remaining).await;
+            }
+
+            editor.edit_response(expired_data()).await
+        });
+
+        Ok((response, expiry))
+    }
+
+    fn page_data(
+        &self,
+        session: &Session<PaginatorState<Source::Context>>,
+        page: Page,
+    ) -> InteractionResponseData {
+        InteractionResponseData {
+            embeds: Some(vec![page.embed]),
+            components: Some(buttons(
+                Some(session.custom_id_with_action(&self.route, PREVIOUS_ACTION)),
+                Some(session.custom_id_with_action(&self.route, NEXT_ACTION)),
+                session.data.page == 0,
+                !page.has_next,
+            )),
+            ..InteractionResponseData::default()
+        }
+    }
+
+    async fn navigate(
+        &self,
+        user_id: Option<Id<UserMarker>>,
+        id: &str,
+        action: Option<&str>,
+    ) -> Result<InteractionResponse, PaginatorError> {
+        let _guard = self.locks.lock(id).await;
+
+        let Some(mut session) = self
+            .sessions
+            .load::<PaginatorState<Source::Context>>(id)
+            .await?
+        else {
+            return Ok(update_message(expired_data()));
+        };
+
+        if user_id != Some(session.data.user_id) {
+            return Ok(ephemeral_response(self.not_invoker_message.to_string()));
+        }
+
+        let index = match action {
+            Some(PREVIOUS_ACTION) => session.data.page.saturating_sub(1),
+            Some(NEXT_ACTION) => session.data.page + 1,
+            _ => session.data.page,
+        };
+
+        let page = self
+            .source
+            .page(&session.data.context, index)
+            .await
+            .ok_or(PaginatorError::PageNotFound(index))?;
+
+        session.data.page = index;
+        self.sessions.save(&session).await?;
+
+        Ok(update_message(self.page_data(&session, page)))
+    }
+}
+
+fn expired_data() -> InteractionResponseData {
+    InteractionResponseData {
+        components: Some(buttons(None, None, true, true)),
+        ..InteractionResponseData::default()
+    }
+}
+
+fn update_message(data: InteractionResponseData) -> InteractionResponse {
+    InteractionResponse {
+        kind: InteractionResponseType::UpdateMessage,
+        data: Some(data),
+    }
+}
+
+fn buttons(
+    previous_id: Option<String>,
+    next_id: Option<String>,
+    previous_disabled: bool,
+    next_disabled: bool,
+) -> Vec<Component> {
+    let button = |custom_id: Option<String>, action: &str, label: &str, disabled: bool| {
+        Component::Button(Button {
+            // Expired buttons still need distinct custom ids
+            custom_id: Some(custom_id.unwrap_or_else(|| format!("expired-{action}"))),
+            disabled,
+            emoji: None,
+            label: Some(label.to_owned()),
+            style: ButtonStyle::Secondary,
+            url: None,
+            sku_id: None,
+        })
+    };
+
+    vec![Component::ActionRow(ActionRow {
+        components: vec![
+            button(previous_id, PREVIOUS_ACTION, "◀", previous_disabled),
+            button(next_id, NEXT_ACTION, "▶", next_disabled),
+        ],
+    })]
+}
+
+impl<Source, Store, Request> Service<Request> for Paginator<Source, Store>
+where
+    Source: PageSource + Send + Sync + 'static,
+    Store: SessionStore + Send + Sync + 'static,
+    Request: AsInteraction,
+{
+    type Response = InteractionResponse;
+    type Error = PaginatorError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let interaction = req.interaction();
+        let user_id = interaction.author_id();
+        let Some((id, action)) = custom_id(interaction).and_then(session_id) else {
+            return Box::pin(std::future::ready(Err(SessionError::MissingId.into())));
+        };
+        let id = id.to_owned();
+        let action = action.map(ToOwned::to_owned);
+        let paginator = self.clone();
+
+        Box::pin(async move { paginator.navigate(user_id, &id, action.as_deref()).await })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::paginator::{embed_page, EmbedPages, Page, PageSource, Paginator, ResponseEditor};
+    use crate::routing::component_router::ComponentRouterService;
+    use crate::session::{MemorySessionStore, Sessions};
+    use crate::{test_utils, BoxFuture};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tower::ServiceExt;
+    use twilight_model::application::interaction::Interaction;
+    use twilight_model::channel::message::{Component, Embed};
+    use twilight_model::http::interaction::{
+        InteractionResponse, InteractionResponseData, InteractionResponseType,
+    };
+    use twilight_model::id::Id;
+
+    fn embed(title: &str) -> Embed {
+        serde_json::from_value(serde_json::json!({ "type": "rich", "title": title })).unwrap()
+    }
+
+    fn buttons(response: &InteractionResponse) -> Vec<(String, bool)> {
+        let components = response.data.as_ref().unwrap().components.as_ref().unwrap();
+        let Component::ActionRow(row) = &components[0] else {
+            panic!("expected action row");
+        };
+
+        row.components
+            .iter()
+            .map(|component| match component {
+                Component::Button(button) => (button.custom_id.clone().unwrap(), button.disabled),
+                _ => panic!("expected button"),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn paginates() {
+        let sessions = Sessions::new(MemorySessionStore::new());
+        let paginator = Paginator::new("page", EmbedPages, sessions, Duration::from_secs(90));
+        let router =
+            ComponentRouterService::new().route::<_, Interaction>("page", paginator.clone());
+
+        let res = paginator
+            .start(Id::new(5), vec![embed("one"), embed("two")])
+            .await
+            .unwrap();
+        let buttons_before = buttons(&res);
+        assert!(buttons_before[0].1);
+        assert!(!buttons_before[1].1);
+
+        let next = &buttons_before[1].0;
+        let res = router
+            .clone()
+            .oneshot(test_utils::click(next, 6))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.kind, InteractionResponseType::ChannelMessageWithSource);
+
+        let res = router
+            .clone()
+            .oneshot(test_utils::click(next, 5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.kind, InteractionResponseType::UpdateMessage);
+        let embeds = res.data.as_ref().unwrap().embeds.as_ref().unwrap();
+        assert_eq!(embeds[0].title.as_deref(), Some("two"));
+        // Custom ids are stable across pages
+        assert_eq!(
+            buttons(&res),
+            vec![(buttons_before[0].0.clone(), false), (next.clone(), true)]
+        );
+
+        let expired = Paginator::new(
+            "page",
+            EmbedPages,
+            Sessions::new(MemorySessionStore::new()),
+            Duration::ZERO,
+        );
+        let res = expired.start(Id::new(5), vec![embed("one")]).await.unwrap();
+        let next = buttons(&res)[1].0.clone();
+        router.mut_route::<_, Interaction>("page", expired);
+        let res = router
+            .oneshot(test_utils::click(&next, 5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(buttons(&res).iter().all(|(_, disabled)| *disabled));
+    }
+
+    /// Yields before returning a page, so concurrent clicks interleave.
+    struct YieldingPages;
+
+    impl PageSource for YieldingPages {
+        type Context = Vec<Embed>;
+
+        fn page<'a>(
+            &'a self,
+            context: &'a Self::Context,
+            index: usize,
+        ) -> BoxFuture<'a, Option<Page>> {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                embed_page(context, index)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn serializes_clicks() {
+        let sessions = Sessions::new(MemorySessionStore::new());
+        let paginator = Paginator::new("page", YieldingPages, sessions, Duration::from_secs(90));
+        let res = paginator
+            .start(Id::new(5), vec![embed("one"), embed("two"), embed("three")])
+            .await
+            .unwrap();
+        let next = &buttons(&res)[1].0;
+
+        let (first, second) = tokio::join!(
+            paginator.clone().oneshot(test_utils::click(next, 5)),
+            paginator.clone().oneshot(test_utils::click(next, 5)),
+        );
+        let titles: Vec<_> = [first, second]
+            .into_iter()
+            .map(|res| res.unwrap().data.unwrap().embeds.unwrap()[0].title.clone())
+            .collect();
+        assert_eq!(titles, [Some("two".to_owned()), Some("three".to_owned())]);
+        assert!(paginator.locks.0.lock().unwrap().is_empty());
+    }
+
+    #[derive(Clone, Default)]
+    struct Edits(Arc<Mutex<Vec<InteractionResponseData>>>);
+
+    impl ResponseEditor for Edits {
+        type Error = ();
+
+        fn edit_response(&self, data: InteractionResponseData) -> BoxFuture<'_, Result<(), ()>> {
+            self.0.lock().unwrap().push(data);
+            Box::pin(std::future::ready(Ok(())))
+        }
+    }
+
+    #[tokio::test]
+    async fn disables_buttons_on_expiry() {
+        let sessions = Sessions::new(MemorySessionStore::new());
+        let paginator = Paginator::new("page", EmbedPages, sessions, Duration::from_millis(10));
+        let edits = Edits::default();
+        let (_, expiry) = paginator
+            .start_with_expiry(Id::new(5), vec![embed("one"), embed("two")], edits.clone())
+            .await
+            .unwrap();
+
+        expiry.await.unwrap();
+        let edits = edits.0.lock().unwrap();
+        let response = InteractionResponse {
+            kind: InteractionResponseType::UpdateMessage,
+            data: Some(edits[0].clone()),
+        };
+        assert!(buttons(&response).iter().all(|(_, disabled)| *disabled));
+    }
+}
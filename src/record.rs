@@ -0,0 +1,314 @@
+use crate::line_writer::LineWriter;
+use crate::request::AsInteraction;
+use crate::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Display;
+use std::fs::OpenOptions;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service, ServiceExt};
+use twilight_model::application::interaction::Interaction;
+
+/// The result of handling a recorded interaction.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedOutcome {
+    Response(Value),
+    Error(String),
+}
+
+/// One line of a recording.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Recording {
+    pub interaction: Interaction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<RecordedOutcome>,
+}
+
+/// Appends every interaction, and optionally its outcome, as a JSON line to a writer on a
+/// background thread.
+///
+/// Errors writing the recording are ignored, so recording never affects handling interactions.
+#[derive(Clone, Debug)]
+pub struct RecordLayer {
+    writer: Arc<LineWriter>,
+    record_outcomes: bool,
+}
+
+impl RecordLayer {
+    #[must_use]
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        RecordLayer {
+            writer: Arc::new(LineWriter::new(writer)),
+            record_outcomes: true,
+        }
+    }
+
+    /// Appends recordings to the file at `path`, creating it if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be opened.
+    pub fn append(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(RecordLayer::new(file))
+    }
+
+    /// Only records interactions, not their outcomes.
+    #[must_use]
+    pub fn without_outcomes(mut self) -> Self {
+        self.record_outcomes = false;
+        self
+    }
+
+    /// Waits until all recordings written so far reached the writer.
+    pub async fn flush(&self) {
+        self.writer.flush().await;
+    }
+}
+
+impl<TService> Layer<TService> for RecordLayer {
+    type Service = RecordService<TService>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        RecordService {
+            inner,
+            writer: Arc::clone(&self.writer),
+            record_outcomes: self.record_outcomes,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordService<Service> {
+    inner: Service,
+    writer: Arc<LineWriter>,
+    record_outcomes: bool,
+}
+
+fn write_recording(writer: &LineWriter, recording: &Recording) {
+    if let Ok(line) = serde_json::to_vec(recording) {
+        writer.write_line(line);
+    }
+}
+
+fn outcome<Response: Serialize, Error: Display>(
+    result: &Result<Response, Error>,
+) -> RecordedOutcome {
+    match result {
+        Ok(response) => serde_json::to_value(response).map_or_else(
+            |error| RecordedOutcome::Error(format!("Unserializable response: {error}")),
+            RecordedOutcome::Response,
+        ),
+        Err(error) => RecordedOutcome::Error(error.to_string()),
+    }
+}
+
+impl<TService, Request> Service<Request> for RecordService<TService>
+where
+    TService: Service<Request>,
+    TService::Response: Serialize + Send + 'static,
+    TService::Error: Display + Send + 'static,
+    TService::Future: Send + 'static,
+    Request: AsInteraction,
+{
+    type Response = TService::Response;
+    type Error = TService::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let interaction = req.interaction().clone();
+        let writer = Arc::clone(&self.writer);
+        let record_outcomes = self.record_outcomes;
+
+        if !record_outcomes {
+            write_recording(
+                &writer,
+                &Recording {
+                    interaction,
+                    outcome: None,
+                },
+            );
+            return Box::pin(self.inner.call(req));
+        }
+
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            let result = future.await;
+            write_recording(
+                &writer,
+                &Recording {
+                    interaction,
+                    outcome: Some(outcome(&result)),
+                },
+            );
+            result
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayError {
+    #[error("Error reading recording")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid recording on line {line}")]
+    Parse {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// A replayed interaction whose outcome differs from the recorded one.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReplayMismatch {
+    /// Line of the recording, starting at 1.
+    pub line: usize,
+    pub expected: RecordedOutcome,
+    pub actual: RecordedOutcome,
+    /// JSON pointers to the values that differ, empty if the outcomes are of different kinds.
+    pub paths: Vec<String>,
+}
+
+/// Feeds every interaction in `recording` through `service`, returning the outcomes that differ
+/// from the recorded ones.
+///
+/// Recordings without an outcome are replayed but not compared.
+///
+/// # Errors
+///
+/// Returns an error if the recording can't be read or contains invalid lines.
+pub async fn replay<S>(
+    recording: impl BufRead,
+    service: S,
+) -> Result<Vec<ReplayMismatch>, ReplayError>
+where
+    S: Service<Interaction> + Clone,
+    S::Response: Serialize,
+    S::Error: Display,
+{
+    let mut mismatches = vec![];
+
+    for (index, line) in recording.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let recording: Recording =
+            serde_json::from_str(&line).map_err(|source| ReplayError::Parse {
+                line: line_number,
+                source,
+            })?;
+
+        let actual = outcome(&service.clone().oneshot(recording.interaction).await);
+
+        let Some(expected) = recording.outcome else {
+            continue;
+        };
+        if expected == actual {
+            continue;
+        }
+
+        let paths = match (&expected, &actual) {
+            (RecordedOutcome::Response(expected), RecordedOutcome::Response(actual)) => {
+                let mut paths = vec![];
+                diff_values(expected, actual, String::new(), &mut paths);
+                paths
+            }
+            _ => vec![],
+        };
+
+        mismatches.push(ReplayMismatch {
+            line: line_number,
+            expected,
+            actual,
+            paths,
+        });
+    }
+
+    Ok(mismatches)
+}
+
+fn diff_values(expected: &Value, actual: &Value, path: String, paths: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            let mut keys: Vec<_> = expected.keys().chain(actual.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                let child = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+                match (expected.get(key), actual.get(key)) {
+                    (Some(expected), Some(actual)) => diff_values(expected, actual, child, paths),
+                    _ => paths.push(child),
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                diff_values(expected, actual, format!("{path}/{index}"), paths);
+            }
+        }
+        _ if expected != actual => paths.push(path),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::record::{replay, RecordLayer, RecordedOutcome};
+    use crate::test_utils;
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+    use tower::{service_fn, Layer, ServiceExt};
+    use twilight_model::application::interaction::Interaction;
+    use twilight_model::id::Id;
+
+    #[tokio::test]
+    async fn records_and_replays() {
+        let buffer = test_utils::SharedBuffer::default();
+        let recording = RecordLayer::new(buffer.clone());
+        let service = recording.layer(service_fn(|interaction: Interaction| async move {
+            if interaction.id == Id::new(2) {
+                Err("failed")
+            } else {
+                Ok(serde_json::json!({ "id": interaction.id, "count": 1 }))
+            }
+        }));
+
+        for id in 1..=2 {
+            let _ = service
+                .clone()
+                .oneshot(test_utils::interaction(Id::new(id)))
+                .await;
+        }
+        recording.flush().await;
+        let recorded = buffer.contents();
+        assert_eq!(String::from_utf8_lossy(&recorded).lines().count(), 2);
+
+        let count = Arc::new(AtomicI64::new(0));
+        let changed = service_fn(move |interaction: Interaction| {
+            let count = count.fetch_add(1, Ordering::Relaxed);
+            async move { Ok::<_, &str>(serde_json::json!({ "id": interaction.id, "count": count })) }
+        });
+
+        let mismatches = replay(recorded.as_slice(), changed).await.unwrap();
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[0].paths, vec!["/count".to_owned()]);
+        assert_eq!(
+            mismatches[1].expected,
+            RecordedOutcome::Error("failed".to_owned())
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use crate::BoxFuture;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use twilight_model::application::interaction::Interaction;
+
+/// An [`Interaction`] together with request-scoped [`Extensions`].
+///
+/// Layers can insert values into the extensions for services further down the stack to use.
+#[derive(Clone, Debug)]
+pub struct InteractionRequest {
+    pub interaction: Interaction,
+    pub extensions: Extensions,
+}
+
+impl InteractionRequest {
+    #[must_use]
+    pub fn new(interaction: Interaction) -> Self {
+        InteractionRequest {
+            interaction,
+            extensions: Extensions::new(),
+        }
+    }
+}
+
+impl From<Interaction> for InteractionRequest {
+    fn from(interaction: Interaction) -> Self {
+        InteractionRequest::new(interaction)
+    }
+}
+
+/// Requests that carry an [`Interaction`].
+pub trait AsInteraction {
+    fn interaction(&self) -> &Interaction;
+
+    /// Returns the request's extensions, if it carries any.
+    fn extensions_mut(&mut self) -> Option<&mut Extensions> {
+        None
+    }
+}
+
+impl AsInteraction for Interaction {
+    fn interaction(&self) -> &Interaction {
+        self
+    }
+}
+
+impl AsInteraction for InteractionRequest {
+    fn interaction(&self) -> &Interaction {
+        &self.interaction
+    }
+
+    fn extensions_mut(&mut self) -> Option<&mut Extensions> {
+        Some(&mut self.extensions)
+    }
+}
+
+/// Requests of route services, which receive the state along with the request.
+impl<State, Request: AsInteraction> AsInteraction for (State, Request) {
+    fn interaction(&self) -> &Interaction {
+        self.1.interaction()
+    }
+
+    fn extensions_mut(&mut self) -> Option<&mut Extensions> {
+        self.1.extensions_mut()
+    }
+}
+
+tokio::task_local! {
+    // The extensions of the request an `InteractionLayerService` is handling
+    static EXTENSIONS: Extensions;
+}
+
+/// Adapts a layer written for services taking [`Interaction`]s to services taking
+/// [`InteractionRequest`]s, such as the before-state layers of a
+/// [`CommandRouterService`](crate::routing::command_router::CommandRouterService).
+///
+/// The extensions of a request are handed on to the service the adapted layer wraps if it is
+/// called from the adapted service's `call` or its future, but not from other tasks.
+#[derive(Clone, Debug)]
+pub struct InteractionLayer<Layer> {
+    layer: Layer,
+}
+
+impl<Layer> InteractionLayer<Layer> {
+    #[must_use]
+    pub fn new(layer: Layer) -> Self {
+        InteractionLayer { layer }
+    }
+}
+
+impl<TLayer, TService> Layer<TService> for InteractionLayer<TLayer>
+where
+    TLayer: Layer<RestoreExtensions<TService>>,
+{
+    type Service = InteractionLayerService<TLayer::Service>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        InteractionLayerService {
+            inner: self.layer.layer(RestoreExtensions { inner }),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct InteractionLayerService<Service> {
+    inner: Service,
+}
+
+impl<TService> Service<InteractionRequest> for InteractionLayerService<TService>
+where
+    TService: Service<Interaction>,
+    TService::Future: Send + 'static,
+{
+    type Response = TService::Response;
+    type Error = TService::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: InteractionRequest) -> Self::Future {
+        let future =
+            EXTENSIONS.sync_scope(req.extensions.clone(), || self.inner.call(req.interaction));
+
+        Box::pin(EXTENSIONS.scope(req.extensions, future))
+    }
+}
+
+/// The service wrapped by the layer adapted with [`InteractionLayer`].
+#[derive(Clone, Debug)]
+pub struct RestoreExtensions<Service> {
+    inner: Service,
+}
+
+impl<TService> Service<Interaction> for RestoreExtensions<TService>
+where
+    TService: Service<InteractionRequest>,
+{
+    type Response = TService::Response;
+    type Error = TService::Error;
+    type Future = TService::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, interaction: Interaction) -> Self::Future {
+        self.inner.call(InteractionRequest {
+            interaction,
+            extensions: EXTENSIONS.try_with(Extensions::clone).unwrap_or_default(),
+        })
+    }
+}
+
+trait AnyClone: Any + Send + Sync {
+    fn clone_box(&self) -> Box<dyn AnyClone>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: Clone + Send + Sync + 'static> AnyClone for T {
+    fn clone_box(&self) -> Box<dyn AnyClone> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+impl Clone for Box<dyn AnyClone> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
+/// A type map of request-scoped values.
+#[derive(Clone, Default)]
+pub struct Extensions {
+    map: HashMap<TypeId, Box<dyn AnyClone>>,
+}
+
+impl Extensions {
+    #[must_use]
+    pub fn new() -> Self {
+        Extensions::default()
+    }
+
+    /// Inserts a value, returning the previous value of the same type.
+    pub fn insert<T: Clone + Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.map
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|previous| previous.into_any().downcast().ok())
+            .map(|previous| *previous)
+    }
+
+    #[must_use]
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|value| (**value).as_any().downcast_ref())
+    }
+
+    pub fn get_mut<T: Send + Sync + 'static>(&mut self) -> Option<&mut T> {
+        self.map
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| (**value).as_any_mut().downcast_mut())
+    }
+
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.map
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.into_any().downcast().ok())
+            .map(|value| *value)
+    }
+
+    #[must_use]
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl Debug for Extensions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.map.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::request::{Extensions, InteractionLayer, InteractionRequest, RestoreExtensions};
+    use crate::test_utils;
+    use std::time::Duration;
+    use tower::layer::layer_fn;
+    use tower::{service_fn, Layer, ServiceExt};
+    use twilight_model::application::interaction::Interaction;
+    use twilight_model::id::Id;
+
+    #[tokio::test]
+    async fn interaction_layer_keeps_extensions_per_request() {
+        // Calls the wrapped service from its future, after the other request started
+        let delay = layer_fn(|inner: RestoreExtensions<_>| {
+            service_fn(move |interaction: Interaction| {
+                let inner = inner.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                    inner.oneshot(interaction).await
+                }
+            })
+        });
+        let service = InteractionLayer::new(delay).layer(service_fn(
+            |request: InteractionRequest| async move {
+                Ok::<_, ()>(request.extensions.get::<i64>().copied())
+            },
+        ));
+
+        let request = |value: i64| {
+            let mut request = InteractionRequest::new(test_utils::interaction(Id::new(1)));
+            request.extensions.insert(value);
+            request
+        };
+
+        let (first, second) = tokio::join!(
+            service.clone().oneshot(request(1)),
+            service.clone().oneshot(request(2)),
+        );
+        assert_eq!((first, second), (Ok(Some(1)), Ok(Some(2))));
+    }
+
+    #[test]
+    fn extensions() {
+        let mut extensions = Extensions::new();
+
+        assert_eq!(extensions.insert(1_i64), None);
+        assert_eq!(extensions.insert(2_i64), Some(1));
+        assert_eq!(extensions.insert("locale"), None);
+
+        *extensions.get_mut::<i64>().unwrap() += 1;
+
+        let cloned = extensions.clone();
+        assert_eq!(cloned.get::<i64>(), Some(&3));
+        assert_eq!(cloned.get::<&str>(), Some(&"locale"));
+        assert_eq!(cloned.get::<u8>(), None);
+
+        assert_eq!(extensions.remove::<i64>(), Some(3));
+        assert!(!extensions.contains::<i64>());
+        assert_eq!(extensions.len(), 1);
+    }
+}
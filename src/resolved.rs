@@ -0,0 +1,269 @@
+use std::fmt::{Display, Formatter};
+// Not semver-stable, see the pinned version in Cargo.toml
+use twilight_interactions::command::internal::{CommandOptionData, CreateOptionData};
+use twilight_interactions::command::{CommandOption, CreateOption};
+use twilight_interactions::error::ParseOptionErrorType;
+use twilight_model::application::command::{self, CommandOptionType};
+use twilight_model::application::interaction::application_command::CommandOptionValue;
+use twilight_model::application::interaction::{
+    Interaction, InteractionChannel, InteractionData, InteractionDataResolved, InteractionMember,
+};
+use twilight_model::channel::{Attachment, Message};
+use twilight_model::guild::Role;
+use twilight_model::id::marker::{
+    AttachmentMarker, ChannelMarker, MessageMarker, RoleMarker, UserMarker,
+};
+use twilight_model::id::Id;
+use twilight_model::user::User;
+
+/// Kind of entity looked up in the resolved data.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ResolvedKind {
+    Attachment,
+    Channel,
+    Member,
+    Message,
+    Role,
+    User,
+}
+
+impl Display for ResolvedKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ResolvedKind::Attachment => "attachment",
+            ResolvedKind::Channel => "channel",
+            ResolvedKind::Member => "member",
+            ResolvedKind::Message => "message",
+            ResolvedKind::Role => "role",
+            ResolvedKind::User => "user",
+        };
+
+        f.write_str(name)
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, thiserror::Error)]
+pub enum ResolveError {
+    #[error("Interaction has no resolved data")]
+    NoResolvedData,
+    #[error("Resolved data has no {kind} {id}")]
+    Missing { kind: ResolvedKind, id: u64 },
+}
+
+/// An option whose id Discord did not include in the resolved data.
+#[derive(Clone, Eq, PartialEq, Debug, thiserror::Error)]
+#[error("Option {field} references {id}, which is missing from the resolved data")]
+pub struct UnresolvedOption {
+    pub field: String,
+    pub id: u64,
+}
+
+/// Looks up entities referenced by command options in the resolved data of an interaction.
+#[derive(Copy, Clone, Debug)]
+pub struct ResolvedData<'a> {
+    resolved: Option<&'a InteractionDataResolved>,
+}
+
+macro_rules! lookup {
+    ($self:ident.$map:ident, $id:expr, $kind:expr) => {
+        $self
+            .resolved
+            .ok_or(ResolveError::NoResolvedData)?
+            .$map
+            .get(&$id)
+            .ok_or(ResolveError::Missing {
+                kind: $kind,
+                id: $id.get(),
+            })
+    };
+}
+
+impl<'a> ResolvedData<'a> {
+    #[must_use]
+    pub fn new(resolved: Option<&'a InteractionDataResolved>) -> Self {
+        ResolvedData { resolved }
+    }
+
+    /// Returns the resolved data of a command or message component interaction.
+    #[must_use]
+    pub fn from_interaction(interaction: &'a Interaction) -> Self {
+        let resolved = match &interaction.data {
+            Some(InteractionData::ApplicationCommand(data)) => data.resolved.as_ref(),
+            Some(InteractionData::MessageComponent(data)) => data.resolved.as_ref(),
+            _ => None,
+        };
+
+        ResolvedData { resolved }
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the attachment is not in the resolved data.
+    pub fn attachment(&self, id: Id<AttachmentMarker>) -> Result<&'a Attachment, ResolveError> {
+        lookup!(self.attachments, id, ResolvedKind::Attachment)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the channel is not in the resolved data.
+    pub fn channel(&self, id: Id<ChannelMarker>) -> Result<&'a InteractionChannel, ResolveError> {
+        lookup!(self.channels, id, ResolvedKind::Channel)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the member is not in the resolved data, e.g. outside of guilds.
+    pub fn member(&self, id: Id<UserMarker>) -> Result<&'a InteractionMember, ResolveError> {
+        lookup!(self.members, id, ResolvedKind::Member)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the message is not in the resolved data.
+    pub fn message(&self, id: Id<MessageMarker>) -> Result<&'a Message, ResolveError> {
+        lookup!(self.messages, id, ResolvedKind::Message)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the role is not in the resolved data.
+    pub fn role(&self, id: Id<RoleMarker>) -> Result<&'a Role, ResolveError> {
+        lookup!(self.roles, id, ResolvedKind::Role)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the user is not in the resolved data.
+    pub fn user(&self, id: Id<UserMarker>) -> Result<&'a User, ResolveError> {
+        lookup!(self.users, id, ResolvedKind::User)
+    }
+}
+
+/// A user option that must resolve to a guild member.
+///
+/// Unlike [`ResolvedUser`](twilight_interactions::command::ResolvedUser), parsing fails if the
+/// user is not a member of the guild. `Role`, `InteractionChannel` and `Attachment` options can be
+/// used directly as [`CommandModel`](twilight_interactions::command::CommandModel) fields.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ResolvedMember {
+    pub user: User,
+    pub member: InteractionMember,
+}
+
+impl CommandOption for ResolvedMember {
+    fn from_option(
+        value: CommandOptionValue,
+        _data: CommandOptionData,
+        resolved: Option<&InteractionDataResolved>,
+    ) -> Result<Self, ParseOptionErrorType> {
+        let CommandOptionValue::User(id) = value else {
+            return Err(ParseOptionErrorType::InvalidType(value.kind()));
+        };
+
+        let resolved = ResolvedData::new(resolved);
+        let lookup_failed = |_| ParseOptionErrorType::LookupFailed(id.get());
+
+        Ok(ResolvedMember {
+            user: resolved.user(id).map_err(lookup_failed)?.clone(),
+            member: resolved.member(id).map_err(lookup_failed)?.clone(),
+        })
+    }
+}
+
+impl CreateOption for ResolvedMember {
+    fn create_option(data: CreateOptionData) -> command::CommandOption {
+        data.into_option(CommandOptionType::User)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command_model_layer::{CommandModelServiceError, CommandRequest};
+    use crate::resolved::{ResolveError, ResolvedKind, ResolvedMember, UnresolvedOption};
+    use crate::routing::command_router::CommandRouterService;
+    use crate::routing::command_service::command_service;
+    use crate::test_utils;
+    use tower::ServiceExt;
+    use twilight_interactions::command::CommandModel;
+    use twilight_model::application::interaction::application_command::CommandOptionValue;
+    use twilight_model::application::interaction::{Interaction, InteractionData};
+    use twilight_model::guild::Role;
+    use twilight_model::id::marker::RoleMarker;
+    use twilight_model::id::Id;
+
+    #[derive(CommandModel)]
+    struct Promote {
+        role: Role,
+    }
+
+    #[derive(CommandModel)]
+    struct Kick {
+        _member: ResolvedMember,
+    }
+
+    fn interaction(name: &str, value: CommandOptionValue) -> Interaction {
+        let mut interaction = test_utils::command_interaction("kick", &[(name, value)]);
+        if let Some(InteractionData::ApplicationCommand(data)) = &mut interaction.data {
+            data.resolved = Some(
+                serde_json::from_value(serde_json::json!({
+                    "roles": {
+                        "3": {
+                            "id": "3",
+                            "name": "mod",
+                            "color": 0,
+                            "hoist": false,
+                            "position": 1,
+                            "permissions": "0",
+                            "managed": false,
+                            "mentionable": false,
+                            "flags": 0,
+                        },
+                    },
+                }))
+                .unwrap(),
+            );
+        }
+        interaction
+    }
+
+    #[tokio::test]
+    async fn resolves_options() {
+        async fn promote(_state: (), request: CommandRequest<Promote>) -> Result<String, ()> {
+            let resolved = request.resolved();
+            assert_eq!(
+                resolved.role(Id::<RoleMarker>::new(4)).unwrap_err(),
+                ResolveError::Missing {
+                    kind: ResolvedKind::Role,
+                    id: 4
+                }
+            );
+
+            Ok(resolved.role(request.model.role.id).unwrap().name.clone())
+        }
+
+        async fn kick(_state: (), _model: Kick) -> Result<String, ()> {
+            Ok(String::new())
+        }
+
+        let router = CommandRouterService::new(())
+            .route(Id::new(1), command_service(promote))
+            .route(Id::new(2), command_service(kick));
+
+        let res = router
+            .clone()
+            .oneshot(interaction("role", CommandOptionValue::Role(Id::new(3))))
+            .await;
+        assert_eq!(res, Ok(Some("mod".to_owned())));
+
+        let mut kick_interaction = interaction("_member", CommandOptionValue::User(Id::new(5)));
+        kick_interaction.id = Id::new(2);
+        let res = router.oneshot(kick_interaction).await;
+        assert_eq!(
+            res,
+            Err(CommandModelServiceError::Unresolved(UnresolvedOption {
+                field: "_member".to_owned(),
+                id: 5,
+            }))
+        );
+    }
+}
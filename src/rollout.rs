@@ -0,0 +1,178 @@
+use crate::request::AsInteraction;
+use crate::BoxFuture;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use twilight_model::application::interaction::Interaction;
+use twilight_model::id::marker::{GuildMarker, UserMarker};
+use twilight_model::id::Id;
+
+/// Decides which interactions are handled by the new version of a service.
+///
+/// An interaction is rolled out if its guild or user is allowlisted, or if its guild falls into
+/// the rolled out percentage of guilds.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct Rollout {
+    pub guilds: Vec<Id<GuildMarker>>,
+    pub users: Vec<Id<UserMarker>>,
+    /// Percentage of guilds, from 0 to 100, rolled out to. Increasing it only ever adds guilds.
+    pub percentage: u8,
+}
+
+impl Rollout {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn guilds(mut self, guilds: impl IntoIterator<Item = Id<GuildMarker>>) -> Self {
+        self.guilds.extend(guilds);
+        self
+    }
+
+    #[must_use]
+    pub fn users(mut self, users: impl IntoIterator<Item = Id<UserMarker>>) -> Self {
+        self.users.extend(users);
+        self
+    }
+
+    /// Sets the percentage of guilds rolled out to, capped at 100.
+    #[must_use]
+    pub fn percentage(mut self, percentage: u8) -> Self {
+        self.percentage = percentage.min(100);
+        self
+    }
+
+    #[must_use]
+    pub fn includes(&self, interaction: &Interaction) -> bool {
+        let guild_included = interaction.guild_id.is_some_and(|guild_id| {
+            self.guilds.contains(&guild_id) || bucket(guild_id) < self.percentage
+        });
+        let user_included = interaction
+            .author_id()
+            .is_some_and(|user_id| self.users.contains(&user_id));
+
+        guild_included || user_included
+    }
+}
+
+/// Maps `guild_id` to a bucket from 0 to 99.
+fn bucket(guild_id: Id<GuildMarker>) -> u8 {
+    // SplitMix64 finalizer, so that consecutive ids are spread over the buckets
+    let mut hash = guild_id.get();
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^= hash >> 31;
+
+    u8::try_from(hash % 100).expect("bucket is below 100")
+}
+
+/// Routes interactions included in a [`Rollout`] to a new service and all others to the wrapped
+/// service, so two versions of a handler can be served behind one route.
+#[derive(Clone, Debug)]
+pub struct RolloutLayer<Service> {
+    new: Service,
+    rollout: Rollout,
+}
+
+impl<Service> RolloutLayer<Service> {
+    #[must_use]
+    pub fn new(new: Service, rollout: Rollout) -> Self {
+        RolloutLayer { new, rollout }
+    }
+}
+
+impl<Old, New: Clone> Layer<Old> for RolloutLayer<New> {
+    type Service = RolloutService<Old, New>;
+
+    fn layer(&self, old: Old) -> Self::Service {
+        RolloutService {
+            old,
+            new: self.new.clone(),
+            rollout: self.rollout.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RolloutService<Old, New> {
+    old: Old,
+    new: New,
+    rollout: Rollout,
+}
+
+impl<Old, New, Request> Service<Request> for RolloutService<Old, New>
+where
+    Old: Service<Request>,
+    Old::Future: Send + 'static,
+    New: Service<Request, Response = Old::Response, Error = Old::Error>,
+    New::Future: Send + 'static,
+    Request: AsInteraction,
+{
+    type Response = Old::Response;
+    type Error = Old::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Which service is called is only known in call, so both need to be ready
+        match self.old.poll_ready(cx) {
+            Poll::Ready(Ok(())) => self.new.poll_ready(cx),
+            other => other,
+        }
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if self.rollout.includes(req.interaction()) {
+            Box::pin(self.new.call(req))
+        } else {
+            Box::pin(self.old.call(req))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command_model_layer::CommandRequest;
+    use crate::rollout::{bucket, Rollout, RolloutLayer};
+    use crate::routing::command_router::CommandRouterService;
+    use crate::routing::command_service::command_service;
+    use crate::test_utils::{self, Empty};
+    use tower::{Layer, ServiceExt};
+    use twilight_model::application::interaction::Interaction;
+    use twilight_model::id::Id;
+
+    #[tokio::test]
+    async fn rolls_out() {
+        async fn old(_state: (), _request: CommandRequest<Empty>) -> Result<i64, ()> {
+            Ok(1)
+        }
+
+        async fn new(_state: (), _request: CommandRequest<Empty>) -> Result<i64, ()> {
+            Ok(2)
+        }
+
+        let rollout = Rollout::new().guilds([Id::new(10)]).percentage(50);
+        let router = CommandRouterService::new(()).route(
+            Id::new(1),
+            RolloutLayer::new(command_service(new), rollout).layer(command_service(old)),
+        );
+
+        let call = |guild_id: Option<u64>| {
+            router.clone().oneshot(Interaction {
+                guild_id: guild_id.map(Id::new),
+                ..test_utils::interaction(Id::new(1))
+            })
+        };
+
+        assert_eq!(call(None).await, Ok(Some(1)));
+        assert_eq!(call(Some(10)).await, Ok(Some(2)));
+
+        let rolled_out = (11..1011).filter(|&id| bucket(Id::new(id)) < 50).count();
+        assert!((400..600).contains(&rolled_out));
+
+        let included = (11..1011).find(|&id| bucket(Id::new(id)) < 50).unwrap();
+        let excluded = (11..1011).find(|&id| bucket(Id::new(id)) >= 50).unwrap();
+        assert_eq!(call(Some(included)).await, Ok(Some(2)));
+        assert_eq!(call(Some(excluded)).await, Ok(Some(1)));
+    }
+}
@@ -1,43 +1,57 @@
-use crate::command_model_layer::{CommandModelLayer, CommandModelServiceError};
+use crate::audit::{AuditLayer, RouteAuditService};
+use crate::command_model_layer::{CommandInput, CommandModelLayer, CommandModelServiceError};
+use crate::request::InteractionRequest;
+use crate::routing::hooks::{
+    CompleteHook, Completion, ErrorHook, InteractionHook, ParseErrorHook, RouteHooks,
+};
+use crate::routing::route::{layer_names, RouteMetadata};
+use crate::routing::route_options::{RouteOptions, RouteOptionsService};
 use crate::routing::InteractionRouterService;
-use crate::state::StateLayer;
+use crate::state::{FromRef, StateLayer};
+use std::any::type_name;
+use std::fmt::{Debug, Display};
+use std::future::Future;
+use std::sync::Arc;
 use std::task::{Context, Poll};
-use tower::util::BoxCloneService;
-use tower::{Layer, Service, ServiceExt};
-use twilight_interactions::command::CommandModel;
+use tower::util::BoxCloneSyncService;
+use tower::{Layer, Service};
+use twilight_interactions::command::CreateCommand;
+use twilight_interactions::error::ParseError;
 use twilight_model::application::interaction::Interaction;
 use twilight_model::id::marker::InteractionMarker;
 use twilight_model::id::Id;
 
-type BoxCommandService<Response, Error> = BoxCloneService<Interaction, Response, Error>;
+type BoxCommandService<Response, Error> = BoxCloneSyncService<InteractionRequest, Response, Error>;
+
+type BoxRouteService<Response, Error> =
+    BoxCommandService<Response, CommandModelServiceError<Error>>;
+
+type Inner<Service, BeforeStateLayer, Base> =
+    InteractionRouterService<Service, (), Id<InteractionMarker>, BeforeStateLayer, Base>;
 
 #[derive(Clone, Debug)]
-pub struct CommandRouterService<State, Layer, Service, BeforeStateLayer> {
+pub struct CommandRouterService<State, Layer, Service, BeforeStateLayer, Base = Service> {
     state: State,
     layer: Layer,
-    inner: InteractionRouterService<Service, BeforeStateLayer>,
+    inner: Inner<Service, BeforeStateLayer, Base>,
 }
 
-impl<State, TLayer, TService, BeforeStateLayer> Service<Interaction>
-    for CommandRouterService<State, TLayer, TService, BeforeStateLayer>
+impl<State, TLayer, TService, BeforeStateLayer, Base> Service<Interaction>
+    for CommandRouterService<State, TLayer, TService, BeforeStateLayer, Base>
 where
-    State: Clone + 'static,
-    TService: Service<Interaction> + Clone + Send + 'static,
-    TService::Response: Send + 'static,
-    TService::Error: Send + 'static,
-    TService::Future: Send,
+    Inner<TService, BeforeStateLayer, Base>: Service<InteractionRequest>,
 {
-    type Response = Option<TService::Response>;
-    type Error = TService::Error;
-    type Future =
-        <InteractionRouterService<TService, BeforeStateLayer> as Service<Interaction>>::Future;
+    type Response =
+        <Inner<TService, BeforeStateLayer, Base> as Service<InteractionRequest>>::Response;
+    type Error = <Inner<TService, BeforeStateLayer, Base> as Service<InteractionRequest>>::Error;
+    type Future = <Inner<TService, BeforeStateLayer, Base> as Service<InteractionRequest>>::Future;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         self.inner.poll_ready(cx)
     }
 
     fn call(&mut self, req: Interaction) -> Self::Future {
-        self.inner.call(req)
+        self.inner.call(InteractionRequest::new(req))
     }
 }
 
@@ -54,86 +68,280 @@ impl<State, Response, Error>
     }
 }
 
-impl<State, TLayer, TService, BeforeStateLayer>
-    CommandRouterService<State, TLayer, TService, BeforeStateLayer>
+impl<State, TLayer, TService, BeforeStateLayer, Response, RouteError>
+    CommandRouterService<
+        State,
+        TLayer,
+        TService,
+        BeforeStateLayer,
+        BoxRouteService<Response, RouteError>,
+    >
 {
     #[must_use]
-    pub fn with_layers(
-        state: State,
-        after_state_layer: TLayer,
-        before_state_layer: BeforeStateLayer,
-    ) -> Self {
-        CommandRouterService {
-            state,
-            layer: after_state_layer,
-            inner: InteractionRouterService::with_layer(before_state_layer),
-        }
+    pub fn route<RouteService, TCommandModel, SubState>(
+        self,
+        id: Id<InteractionMarker>,
+        service: RouteService,
+    ) -> Self
+    where
+        State: Clone + Send + Sync + 'static,
+        SubState: FromRef<State> + 'static,
+        TLayer: Layer<RouteService>,
+        TLayer::Service: Service<(SubState, TCommandModel), Response = Response, Error = RouteError>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        <TLayer::Service as Service<(SubState, TCommandModel)>>::Future: Send,
+        Response: Send + 'static,
+        RouteError: Send + 'static,
+        TCommandModel: CommandInput + Send + 'static,
+    {
+        self.mut_route(id, service);
+        self
     }
 
+    pub fn mut_route<RouteService, TCommandModel, SubState>(
+        &self,
+        id: Id<InteractionMarker>,
+        service: RouteService,
+    ) -> Option<BoxRouteService<Response, RouteError>>
+    where
+        State: Clone + Send + Sync + 'static,
+        SubState: FromRef<State> + 'static,
+        TLayer: Layer<RouteService>,
+        TLayer::Service: Service<(SubState, TCommandModel), Response = Response, Error = RouteError>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        <TLayer::Service as Service<(SubState, TCommandModel)>>::Future: Send,
+        Response: Send + 'static,
+        RouteError: Send + 'static,
+        TCommandModel: CommandInput + Send + 'static,
+    {
+        self.mut_route_with(id, service, RouteOptions::default())
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `options` are not [valid](RouteOptions::validate).
     #[must_use]
-    pub fn route<RouteService, TCommandModel>(
-        mut self,
+    pub fn route_with<RouteService, TCommandModel, SubState>(
+        self,
         id: Id<InteractionMarker>,
         service: RouteService,
+        options: RouteOptions,
     ) -> Self
     where
-        State: Clone + Send + 'static,
+        State: Clone + Send + Sync + 'static,
+        SubState: FromRef<State> + 'static,
         TLayer: Layer<RouteService>,
-        TLayer::Service: Service<(State, TCommandModel)> + Clone + Send + 'static,
-        <TLayer::Service as Service<(State, TCommandModel)>>::Future: Send,
-        <TLayer::Service as Service<(State, TCommandModel)>>::Response: 'static,
-        <TLayer::Service as Service<(State, TCommandModel)>>::Error: 'static,
-        TCommandModel: CommandModel + Send + 'static,
-        TService: Service<Interaction>,
-        BeforeStateLayer: Layer<
-            BoxCommandService<
-                <TLayer::Service as Service<(State, TCommandModel)>>::Response,
-                CommandModelServiceError<
-                    <TLayer::Service as Service<(State, TCommandModel)>>::Error,
-                >,
-            >,
-            Service = TService,
-        >,
+        TLayer::Service: Service<(SubState, TCommandModel), Response = Response, Error = RouteError>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        <TLayer::Service as Service<(SubState, TCommandModel)>>::Future: Send,
+        Response: Send + 'static,
+        RouteError: Send + 'static,
+        TCommandModel: CommandInput + Send + 'static,
     {
-        self.mut_route(id, service);
+        self.mut_route_with(id, service, options);
         self
     }
 
-    pub fn mut_route<RouteService, TCommandModel>(
-        &mut self,
+    /// # Panics
+    ///
+    /// Panics if `options` are not [valid](RouteOptions::validate).
+    pub fn mut_route_with<RouteService, TCommandModel, SubState>(
+        &self,
         id: Id<InteractionMarker>,
         service: RouteService,
-    ) -> Option<TService>
+        options: RouteOptions,
+    ) -> Option<BoxRouteService<Response, RouteError>>
     where
-        State: Clone + Send + 'static,
+        State: Clone + Send + Sync + 'static,
+        SubState: FromRef<State> + 'static,
         TLayer: Layer<RouteService>,
-        TLayer::Service: Service<(State, TCommandModel)> + Clone + Send + 'static,
-        <TLayer::Service as Service<(State, TCommandModel)>>::Future: Send,
-        <TLayer::Service as Service<(State, TCommandModel)>>::Response: 'static,
-        <TLayer::Service as Service<(State, TCommandModel)>>::Error: 'static,
-        TCommandModel: CommandModel + Send + 'static,
-        TService: Service<Interaction>,
-        BeforeStateLayer: Layer<
-            BoxCommandService<
-                <TLayer::Service as Service<(State, TCommandModel)>>::Response,
-                CommandModelServiceError<
-                    <TLayer::Service as Service<(State, TCommandModel)>>::Error,
-                >,
-            >,
-            Service = TService,
-        >,
+        TLayer::Service: Service<(SubState, TCommandModel), Response = Response, Error = RouteError>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        <TLayer::Service as Service<(SubState, TCommandModel)>>::Future: Send,
+        Response: Send + 'static,
+        RouteError: Send + 'static,
+        TCommandModel: CommandInput + Send + 'static,
+    {
+        self.insert_route(id, service, options, None)
+    }
+
+    /// Adds a route like [`route_with`](Self::route_with) which writes an audit record for every
+    /// call, including calls rejected by the route options.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `options` are not [valid](RouteOptions::validate).
+    #[must_use]
+    pub fn route_audited<RouteService, TCommandModel, SubState>(
+        self,
+        id: Id<InteractionMarker>,
+        service: RouteService,
+        options: RouteOptions,
+        audit: AuditLayer,
+    ) -> Self
+    where
+        State: Clone + Send + Sync + 'static,
+        SubState: FromRef<State> + 'static,
+        TLayer: Layer<RouteService>,
+        TLayer::Service: Service<(SubState, TCommandModel), Response = Response, Error = RouteError>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        <TLayer::Service as Service<(SubState, TCommandModel)>>::Future: Send,
+        Response: Send + 'static,
+        RouteError: Display + Send + 'static,
+        TCommandModel: CommandInput + Send + 'static,
+    {
+        self.insert_route(id, service, options, Some((audit, ToString::to_string)));
+        self
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn insert_route<RouteService, TCommandModel, SubState>(
+        &self,
+        id: Id<InteractionMarker>,
+        service: RouteService,
+        options: RouteOptions,
+        audit: Option<(AuditLayer, fn(&RouteError) -> String)>,
+    ) -> Option<BoxRouteService<Response, RouteError>>
+    where
+        State: Clone + Send + Sync + 'static,
+        SubState: FromRef<State> + 'static,
+        TLayer: Layer<RouteService>,
+        TLayer::Service: Service<(SubState, TCommandModel), Response = Response, Error = RouteError>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        <TLayer::Service as Service<(SubState, TCommandModel)>>::Future: Send,
+        Response: Send + 'static,
+        RouteError: Send + 'static,
+        TCommandModel: CommandInput + Send + 'static,
     {
         let layered = (
             CommandModelLayer::new(),
-            StateLayer::new(self.state.clone()),
+            StateLayer::with_substate(self.state.clone()),
             &self.layer,
         )
-            .layer(service)
-            .boxed_clone();
+            .layer(service);
+
+        let installation = options.installation.clone();
+        let audited = audit.is_some();
+        let layered = RouteOptionsService::new(layered, options)
+            .unwrap_or_else(|error| panic!("invalid options for route {id}: {error}"));
+        let hooks = Arc::clone(self.inner.hooks());
+        let layered = match audit {
+            Some((audit, describe)) => BoxCommandService::new(RouteHooks::new(
+                RouteAuditService::new(layered, audit, describe),
+                hooks,
+            )),
+            None => BoxCommandService::new(RouteHooks::new(layered, hooks)),
+        };
+
+        let metadata = RouteMetadata {
+            model: Some(type_name::<TCommandModel>()),
+            layers: layer_names::<TLayer>(),
+            installation,
+            audited,
+            ..RouteMetadata::default()
+        };
 
-        self.inner.mut_route(id, layered)
+        self.inner.mut_route_with_metadata(id, layered, metadata)
+    }
+
+    /// Runs `hook` when a route fails, with the error returned by the route before any layers
+    /// added with [`layer`](Self::layer).
+    #[must_use]
+    pub fn on_error<F, Fut>(self, hook: F) -> Self
+    where
+        F: Fn(&Interaction, &CommandModelServiceError<RouteError>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+        RouteError: 'static,
+    {
+        let hook: ErrorHook<CommandModelServiceError<RouteError>> =
+            Arc::new(move |interaction, error| Box::pin(hook(interaction, error)));
+        self.inner
+            .add_hooks(|hooks| hooks.push_error(Arc::clone(&hook)));
+        self
+    }
+}
+
+impl<State, TLayer, TService, BeforeStateLayer, Base>
+    CommandRouterService<State, TLayer, TService, BeforeStateLayer, Base>
+{
+    #[must_use]
+    pub fn with_layers(
+        state: State,
+        after_state_layer: TLayer,
+        before_state_layer: BeforeStateLayer,
+    ) -> Self {
+        CommandRouterService {
+            state,
+            layer: after_state_layer,
+            inner: InteractionRouterService::with_layers((), before_state_layer),
+        }
+    }
+
+    pub fn remove_route(&self, id: Id<InteractionMarker>) -> Option<Base>
+    where
+        Base: Clone,
+    {
+        self.inner.remove_route(&id)
+    }
+
+    #[must_use]
+    pub fn contains_route(&self, id: Id<InteractionMarker>) -> bool {
+        self.inner.contains_route(&id)
+    }
+
+    /// Returns a snapshot of the registered routes and their metadata.
+    pub fn routes(&self) -> impl Iterator<Item = (Id<InteractionMarker>, RouteMetadata)> {
+        self.inner.routes()
+    }
+
+    /// Sets the name and description of the route for `id`, returning whether such a route exists.
+    pub fn describe_route(
+        &self,
+        id: Id<InteractionMarker>,
+        name: impl Into<String>,
+        description: impl Into<String>,
+    ) -> bool {
+        self.inner.describe_route(&id, name, description)
+    }
+
+    /// Describes the route for `id` with the name and description of `Command`, returning whether
+    /// such a route exists.
+    pub fn describe_route_from<Command: CreateCommand>(&self, id: Id<InteractionMarker>) -> bool {
+        self.describe_route(id, Command::NAME, Command::create_command().description)
+    }
+
+    /// Returns a router sharing this router's routes which accepts [`InteractionRequest`]s, so
+    /// extensions can be provided from outside the router.
+    #[must_use]
+    pub fn interaction_router(&self) -> Inner<TService, BeforeStateLayer, Base>
+    where
+        BeforeStateLayer: Clone,
+    {
+        self.inner.clone()
     }
 
+    /// Wraps every route in `new_layer`, which runs before the state is extracted.
+    ///
+    /// Route services take [`InteractionRequest`]s; layers written for [`Interaction`]s can be
+    /// adapted with [`InteractionLayer`](crate::request::InteractionLayer).
     #[must_use]
     pub fn layer<NewBeforeStateLayer>(
         self,
@@ -143,6 +351,7 @@ impl<State, TLayer, TService, BeforeStateLayer>
         TLayer,
         NewBeforeStateLayer::Service,
         (NewBeforeStateLayer, BeforeStateLayer),
+        Base,
     >
     where
         NewBeforeStateLayer: Layer<TService>,
@@ -153,25 +362,197 @@ impl<State, TLayer, TService, BeforeStateLayer>
             inner: self.inner.layer(new_layer),
         }
     }
+
+    /// Runs `hook` before an interaction is dispatched to its route.
+    #[must_use]
+    pub fn on_dispatch<F, Fut>(self, hook: F) -> Self
+    where
+        F: Fn(&Interaction) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let hook: InteractionHook = Arc::new(move |interaction| Box::pin(hook(interaction)));
+        self.inner
+            .add_hooks(|hooks| hooks.push_dispatch(Arc::clone(&hook)));
+        self
+    }
+
+    /// Runs `hook` for interactions without a matching route.
+    #[must_use]
+    pub fn on_unmatched<F, Fut>(self, hook: F) -> Self
+    where
+        F: Fn(&Interaction) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let hook: InteractionHook = Arc::new(move |interaction| Box::pin(hook(interaction)));
+        self.inner
+            .add_hooks(|hooks| hooks.push_unmatched(Arc::clone(&hook)));
+        self
+    }
+
+    /// Runs `hook` when the command data of an interaction can't be parsed into its route's model.
+    ///
+    /// The error is afterwards also passed to the `on_error` hooks.
+    #[must_use]
+    pub fn on_parse_error<F, Fut>(self, hook: F) -> Self
+    where
+        F: Fn(&Interaction, &ParseError) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let hook: ParseErrorHook =
+            Arc::new(move |interaction, error| Box::pin(hook(interaction, error)));
+        self.inner
+            .add_hooks(|hooks| hooks.push_parse_error(Arc::clone(&hook)));
+        self
+    }
+
+    /// Runs `hook` after every interaction, with its outcome and how long it took.
+    #[must_use]
+    pub fn on_complete<F, Fut>(self, hook: F) -> Self
+    where
+        F: Fn(&Interaction, &Completion) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let hook: CompleteHook =
+            Arc::new(move |interaction, completion| Box::pin(hook(interaction, completion)));
+        self.inner
+            .add_hooks(|hooks| hooks.push_complete(Arc::clone(&hook)));
+        self
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::command_model_layer::CommandModelServiceError;
+    use crate::command_model_layer::{CommandModelServiceError, CommandRequest};
+    use crate::request::{InteractionLayer, InteractionRequest};
     use crate::routing::command_router::CommandRouterService;
     use crate::routing::command_service::command_service;
-    use crate::test_utils;
+    use crate::state::FromRef;
+    use crate::test_utils::{self, Empty};
     use std::sync::atomic::{AtomicI64, Ordering};
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
     use tower::util::{MapRequestLayer, MapResponseLayer};
     use tower::{service_fn, Service, ServiceExt};
     use twilight_interactions::command::CommandModel;
+    use twilight_model::application::interaction::Interaction;
     use twilight_model::id::Id;
 
     #[derive(CommandModel)]
     struct HasCommandModelA {}
     #[derive(CommandModel)]
     struct HasCommandModelB {}
+    #[derive(CommandModel)]
+    struct RequiresOption {
+        #[allow(dead_code)]
+        text: String,
+    }
+
+    #[tokio::test]
+    async fn hooks() {
+        async fn command(_state: (), _model: HasCommandModelA) -> Result<i64, ()> {
+            Ok(1)
+        }
+
+        async fn command2(_state: (), _model: RequiresOption) -> Result<i64, ()> {
+            Ok(2)
+        }
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let record = |name: &'static str| {
+            let events = Arc::clone(&events);
+            move |interaction: &Interaction| {
+                events
+                    .lock()
+                    .unwrap()
+                    .push(format!("{name} {}", interaction.id));
+                async {}
+            }
+        };
+        let parse_events = Arc::clone(&events);
+        let error_events = Arc::clone(&events);
+        let complete_events = Arc::clone(&events);
+
+        let router = CommandRouterService::new(())
+            .route(Id::new(1), command_service(command))
+            .route(Id::new(2), command_service(command2));
+        // Hooks added later are shared with existing clones
+        let worker = router.clone();
+        let router = router
+            .on_dispatch(record("dispatch"))
+            .on_unmatched(record("unmatched"))
+            .on_parse_error(move |interaction, _error| {
+                parse_events
+                    .lock()
+                    .unwrap()
+                    .push(format!("parse_error {}", interaction.id));
+                async {}
+            })
+            .on_error(move |interaction, error| {
+                assert!(matches!(error, CommandModelServiceError::Parse(_)));
+                error_events
+                    .lock()
+                    .unwrap()
+                    .push(format!("error {}", interaction.id));
+                async {}
+            })
+            .on_complete(move |interaction, completion| {
+                complete_events.lock().unwrap().push(format!(
+                    "complete {} {:?}",
+                    interaction.id, completion.outcome
+                ));
+                async {}
+            });
+
+        for id in 1..=3 {
+            let _ = worker
+                .clone()
+                .oneshot(test_utils::interaction(Id::new(id)))
+                .await;
+        }
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "dispatch 1",
+                "complete 1 Handled",
+                "dispatch 2",
+                "parse_error 2",
+                "error 2",
+                "complete 2 Failed",
+                "dispatch 3",
+                "unmatched 3",
+                "complete 3 Unmatched",
+            ]
+        );
+
+        events.lock().unwrap().clear();
+        let request = InteractionRequest::new(test_utils::interaction(Id::new(2)));
+        let _ = router.interaction_router().oneshot(request).await;
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "dispatch 2",
+                "parse_error 2",
+                "error 2",
+                "complete 2 Failed"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn errors_without_debug() {
+        struct Failed;
+
+        async fn command(_state: (), _model: HasCommandModelA) -> Result<i64, Failed> {
+            Err(Failed)
+        }
+
+        let router = CommandRouterService::new(()).route(Id::new(1), command_service(command));
+        let res = router.oneshot(test_utils::interaction(Id::new(1))).await;
+        assert!(matches!(
+            res,
+            Err(CommandModelServiceError::Service(Failed))
+        ));
+    }
 
     #[tokio::test]
     async fn command_service_test() {
@@ -210,6 +591,119 @@ mod test {
         assert_eq!(res2, 2);
     }
 
+    #[tokio::test]
+    async fn substate() {
+        #[derive(Clone)]
+        struct AppState {
+            config: i64,
+            _pool: Arc<()>,
+        }
+
+        impl FromRef<AppState> for i64 {
+            fn from_ref(input: &AppState) -> Self {
+                input.config
+            }
+        }
+
+        async fn command(config: i64, _model: HasCommandModelA) -> Result<i64, ()> {
+            Ok(config)
+        }
+
+        let state = AppState {
+            config: 7,
+            _pool: Arc::new(()),
+        };
+
+        let res = CommandRouterService::new(state)
+            .route(Id::new(1), command_service(command))
+            .oneshot(test_utils::interaction(Id::new(1)))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, 7);
+    }
+
+    #[tokio::test]
+    async fn extensions() {
+        #[derive(Clone)]
+        struct Locale(&'static str);
+
+        async fn command(
+            _state: (),
+            request: CommandRequest<HasCommandModelA>,
+        ) -> Result<(&'static str, i64, Option<String>), ()> {
+            Ok((
+                request.extension::<Locale>().unwrap().0,
+                *request.extension::<i64>().unwrap(),
+                request.interaction.locale,
+            ))
+        }
+
+        let router = CommandRouterService::new(())
+            .route(Id::new(1), command_service(command))
+            .layer(MapRequestLayer::new(|mut request: InteractionRequest| {
+                request.extensions.insert(Locale("de"));
+                request
+            }))
+            .layer(InteractionLayer::new(MapRequestLayer::new(
+                |mut interaction: Interaction| {
+                    interaction.locale = Some("fr".to_owned());
+                    interaction
+                },
+            )));
+
+        let mut request = InteractionRequest::new(test_utils::interaction(Id::new(1)));
+        request.extensions.insert(5_i64);
+
+        let res = router
+            .interaction_router()
+            .oneshot(request)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(res, ("de", 5, Some("fr".to_owned())));
+    }
+
+    #[test]
+    fn route_introspection() {
+        async fn command(_state: (), _model: HasCommandModelA) -> Result<i64, ()> {
+            Ok(1)
+        }
+
+        async fn empty(_state: (), _model: Empty) -> Result<i64, ()> {
+            Ok(2)
+        }
+
+        let router = CommandRouterService::new(())
+            .route(Id::new(1), command_service(command))
+            .route(Id::new(2), command_service(command))
+            .route(Id::new(3), command_service(empty));
+
+        assert!(router.describe_route(Id::new(1), "a", "Command A"));
+        assert!(!router.describe_route(Id::new(4), "c", "Command C"));
+        assert!(router.describe_route_from::<Empty>(Id::new(3)));
+
+        let mut described: Vec<_> = router.routes().collect();
+        described.sort_by_key(|(id, _)| *id);
+
+        assert_eq!(described.len(), 3);
+        assert_eq!(described[0].1.name.as_deref(), Some("a"));
+        assert_eq!(described[0].1.description.as_deref(), Some("Command A"));
+        assert_eq!(described[1].1.name, None);
+        assert!(described[1].1.model.unwrap().ends_with("HasCommandModelA"));
+        assert_eq!(described[2].1.name.as_deref(), Some("empty"));
+        assert_eq!(
+            described[2].1.description.as_deref(),
+            Some("Takes no options")
+        );
+
+        assert!(router.remove_route(Id::new(2)).is_some());
+        assert!(!router.contains_route(Id::new(2)));
+        assert!(router.contains_route(Id::new(1)));
+    }
+
     #[tokio::test]
     async fn test_layers() {
         struct Mapped<S>(S);
@@ -0,0 +1,196 @@
+use crate::custom_id::{CustomId, CustomIdLayer, CustomIdService};
+use crate::routing::route::{RouteKey, RouteMetadata};
+use crate::routing::InteractionRouterService;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use twilight_model::application::interaction::{Interaction, InteractionData};
+
+/// Separates the route name from the rest of a component custom id.
+pub const CUSTOM_ID_SEPARATOR: char = ':';
+
+/// Route key of message component and modal submit interactions, the part of the custom id before
+/// the first [`CUSTOM_ID_SEPARATOR`].
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+pub struct ComponentKey(pub String);
+
+impl RouteKey for ComponentKey {
+    fn from_interaction(interaction: &Interaction) -> Option<Self> {
+        custom_id(interaction)
+            .map(|custom_id| ComponentKey(split_custom_id(custom_id).0.to_owned()))
+    }
+}
+
+/// Returns the custom id of a message component or modal submit interaction.
+#[must_use]
+pub fn custom_id(interaction: &Interaction) -> Option<&str> {
+    match &interaction.data {
+        Some(InteractionData::MessageComponent(data)) => Some(&data.custom_id),
+        Some(InteractionData::ModalSubmit(data)) => Some(&data.custom_id),
+        _ => None,
+    }
+}
+
+/// Splits a custom id into its route name and the remaining data, if any.
+#[must_use]
+pub fn split_custom_id(custom_id: &str) -> (&str, Option<&str>) {
+    match custom_id.split_once(CUSTOM_ID_SEPARATOR) {
+        Some((name, rest)) => (name, Some(rest)),
+        None => (custom_id, None),
+    }
+}
+
+/// Routes message component and modal submit interactions by the route name in their custom id.
+#[derive(Debug)]
+pub struct ComponentRouterService<Service, Layer = (), Outer = (), Base = Service> {
+    inner: InteractionRouterService<Service, Layer, ComponentKey, Outer, Base>,
+}
+
+impl<TService, TLayer: Clone, Outer: Clone, Base> Clone
+    for ComponentRouterService<TService, TLayer, Outer, Base>
+{
+    fn clone(&self) -> Self {
+        ComponentRouterService {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<TService, TLayer: Default> Default for ComponentRouterService<TService, TLayer> {
+    fn default() -> Self {
+        ComponentRouterService {
+            inner: InteractionRouterService::default(),
+        }
+    }
+}
+
+type Inner<Service, Layer, Outer, Base> =
+    InteractionRouterService<Service, Layer, ComponentKey, Outer, Base>;
+
+impl<TService, TLayer, Outer, Base, Request> Service<Request>
+    for ComponentRouterService<TService, TLayer, Outer, Base>
+where
+    Inner<TService, TLayer, Outer, Base>: Service<Request>,
+{
+    type Response = <Inner<TService, TLayer, Outer, Base> as Service<Request>>::Response;
+    type Error = <Inner<TService, TLayer, Outer, Base> as Service<Request>>::Error;
+    type Future = <Inner<TService, TLayer, Outer, Base> as Service<Request>>::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.inner.call(req)
+    }
+}
+
+impl<TService> ComponentRouterService<TService> {
+    #[must_use]
+    pub fn new() -> Self {
+        ComponentRouterService::default()
+    }
+}
+
+impl<TService, TLayer> ComponentRouterService<TService, TLayer> {
+    #[must_use]
+    pub fn with_layer(layer: TLayer) -> Self {
+        ComponentRouterService {
+            inner: InteractionRouterService::with_layer(layer),
+        }
+    }
+}
+
+impl<TService, TLayer, Outer, Base> ComponentRouterService<TService, TLayer, Outer, Base> {
+    /// # Panics
+    ///
+    /// Panics if `name` contains [`CUSTOM_ID_SEPARATOR`].
+    #[must_use]
+    pub fn route<RouteService, Request>(
+        self,
+        name: impl Into<String>,
+        service: RouteService,
+    ) -> Self
+    where
+        TLayer: Layer<RouteService, Service = Base>,
+        Base: Clone,
+        RouteService: Service<Request>,
+    {
+        self.mut_route(name, service);
+        self
+    }
+
+    /// Routes custom ids of `T` to `service`, which is called with the decoded custom id and the
+    /// request.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`T::ROUTE`](CustomId::ROUTE) contains [`CUSTOM_ID_SEPARATOR`].
+    #[must_use]
+    pub fn route_custom_id<T, RouteService, Request>(self, service: RouteService) -> Self
+    where
+        T: CustomId,
+        TLayer: Layer<CustomIdService<RouteService, T>, Service = Base>,
+        Base: Clone,
+        CustomIdService<RouteService, T>: Service<Request>,
+    {
+        self.mut_route(T::ROUTE, CustomIdLayer::new().layer(service));
+        self
+    }
+
+    /// Adds or replaces the route for custom ids starting with `name`, returning the previously
+    /// routed service.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` contains [`CUSTOM_ID_SEPARATOR`], as such a route could never match.
+    pub fn mut_route<RouteService, Request>(
+        &self,
+        name: impl Into<String>,
+        service: RouteService,
+    ) -> Option<Base>
+    where
+        TLayer: Layer<RouteService, Service = Base>,
+        Base: Clone,
+        RouteService: Service<Request>,
+    {
+        let name = name.into();
+        assert!(
+            !name.contains(CUSTOM_ID_SEPARATOR),
+            "component route {name} contains the custom id separator"
+        );
+
+        self.inner.mut_route(ComponentKey(name), service)
+    }
+
+    pub fn remove_route(&self, name: &str) -> Option<Base>
+    where
+        Base: Clone,
+    {
+        self.inner.remove_route(&ComponentKey(name.to_owned()))
+    }
+
+    #[must_use]
+    pub fn contains_route(&self, name: &str) -> bool {
+        self.inner.contains_route(&ComponentKey(name.to_owned()))
+    }
+
+    /// Returns a snapshot of the registered route names and their metadata.
+    pub fn routes(&self) -> impl Iterator<Item = (String, RouteMetadata)> {
+        self.inner
+            .routes()
+            .map(|(ComponentKey(name), metadata)| (name, metadata))
+    }
+
+    #[must_use]
+    pub fn layer<NewLayer>(
+        self,
+        layer: NewLayer,
+    ) -> ComponentRouterService<NewLayer::Service, TLayer, (NewLayer, Outer), Base>
+    where
+        NewLayer: Layer<TService>,
+    {
+        ComponentRouterService {
+            inner: self.inner.layer(layer),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use crate::command_model_layer::CommandModelServiceError;
+use crate::request::InteractionRequest;
+use crate::resolved::UnresolvedOption;
+use crate::BoxFuture;
+use arc_swap::ArcSwap;
+use std::any::Any;
+use std::borrow::Cow;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tower::Service;
+use twilight_interactions::error::{ParseError, ParseOptionError, ParseOptionErrorType};
+use twilight_model::application::interaction::Interaction;
+
+pub type HookFuture = BoxFuture<'static, ()>;
+
+pub(crate) type InteractionHook = Arc<dyn Fn(&Interaction) -> HookFuture + Send + Sync>;
+pub(crate) type ParseErrorHook = Arc<dyn Fn(&Interaction, &ParseError) -> HookFuture + Send + Sync>;
+pub(crate) type ErrorHook<Error> = Arc<dyn Fn(&Interaction, &Error) -> HookFuture + Send + Sync>;
+pub(crate) type CompleteHook = Arc<dyn Fn(&Interaction, &Completion) -> HookFuture + Send + Sync>;
+
+/// How the router finished handling an interaction.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum DispatchOutcome {
+    Handled,
+    Unmatched,
+    Failed,
+}
+
+/// Passed to `on_complete` hooks once an interaction was handled.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Completion {
+    pub outcome: DispatchOutcome,
+    pub elapsed: Duration,
+}
+
+/// Async callbacks run by a [`CommandRouterService`](super::command_router::CommandRouterService)
+/// around dispatching interactions.
+///
+/// Hooks are awaited in order before the router's future completes, so they should be cheap or
+/// hand their work off. The returned futures can't borrow the arguments, clone what you need.
+#[derive(Clone, Default)]
+pub struct Hooks {
+    dispatch: Vec<InteractionHook>,
+    unmatched: Vec<InteractionHook>,
+    parse_error: Vec<ParseErrorHook>,
+    // The `ErrorHook`s of the router's route error type
+    error: Vec<Arc<dyn Any + Send + Sync>>,
+    complete: Vec<CompleteHook>,
+}
+
+impl Debug for Hooks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hooks")
+            .field("dispatch", &self.dispatch.len())
+            .field("unmatched", &self.unmatched.len())
+            .field("parse_error", &self.parse_error.len())
+            .field("error", &self.error.len())
+            .field("complete", &self.complete.len())
+            .finish()
+    }
+}
+
+impl Hooks {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.dispatch.is_empty()
+            && self.unmatched.is_empty()
+            && self.parse_error.is_empty()
+            && self.error.is_empty()
+            && self.complete.is_empty()
+    }
+
+    pub(crate) fn push_dispatch(&mut self, hook: InteractionHook) {
+        self.dispatch.push(hook);
+    }
+
+    pub(crate) fn push_unmatched(&mut self, hook: InteractionHook) {
+        self.unmatched.push(hook);
+    }
+
+    pub(crate) fn push_parse_error(&mut self, hook: ParseErrorHook) {
+        self.parse_error.push(hook);
+    }
+
+    pub(crate) fn push_error<Error: 'static>(&mut self, hook: ErrorHook<Error>) {
+        self.error.push(Arc::new(hook));
+    }
+
+    pub(crate) fn push_complete(&mut self, hook: CompleteHook) {
+        self.complete.push(hook);
+    }
+
+    // The hooks are all called before awaiting any of them so that the returned future doesn't
+    // borrow the arguments
+    pub(crate) fn dispatch(&self, interaction: &Interaction) -> HookFuture {
+        run(self.dispatch.iter().map(|hook| hook(interaction)))
+    }
+
+    pub(crate) fn unmatched(&self, interaction: &Interaction) -> HookFuture {
+        run(self.unmatched.iter().map(|hook| hook(interaction)))
+    }
+
+    pub(crate) fn parse_error(&self, interaction: &Interaction, error: &ParseError) -> HookFuture {
+        run(self.parse_error.iter().map(|hook| hook(interaction, error)))
+    }
+
+    pub(crate) fn error<Error: 'static>(
+        &self,
+        interaction: &Interaction,
+        error: &Error,
+    ) -> HookFuture {
+        run(self
+            .error
+            .iter()
+            .filter_map(|hook| hook.downcast_ref::<ErrorHook<Error>>())
+            .map(|hook| hook(interaction, error)))
+    }
+
+    pub(crate) fn complete(
+        &self,
+        interaction: &Interaction,
+        completion: &Completion,
+    ) -> HookFuture {
+        run(self
+            .complete
+            .iter()
+            .map(|hook| hook(interaction, completion)))
+    }
+}
+
+fn run(futures: impl Iterator<Item = HookFuture>) -> HookFuture {
+    let futures: Vec<_> = futures.collect();
+
+    Box::pin(async move {
+        for future in futures {
+            future.await;
+        }
+    })
+}
+
+/// Runs the parse error and error hooks of a router for one of its routes, where the error still
+/// has the route's type.
+#[derive(Clone, Debug)]
+pub(crate) struct RouteHooks<Service> {
+    inner: Service,
+    hooks: Arc<ArcSwap<Hooks>>,
+}
+
+impl<Service> RouteHooks<Service> {
+    pub(crate) fn new(inner: Service, hooks: Arc<ArcSwap<Hooks>>) -> Self {
+        RouteHooks { inner, hooks }
+    }
+}
+
+impl<TService, ServiceError> Service<InteractionRequest> for RouteHooks<TService>
+where
+    TService: Service<InteractionRequest, Error = CommandModelServiceError<ServiceError>>,
+    TService::Response: Send + 'static,
+    TService::Future: Send + 'static,
+    ServiceError: Send + 'static,
+{
+    type Response = TService::Response;
+    type Error = TService::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: InteractionRequest) -> Self::Future {
+        let hooks = self.hooks.load_full();
+        let interaction = (!hooks.parse_error.is_empty() || !hooks.error.is_empty())
+            .then(|| req.interaction.clone());
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            let result = future.await;
+
+            // The hooks are called before awaiting them, so the result needn't be Sync
+            let called = match (&result, &interaction) {
+                (Err(error), Some(interaction)) => {
+                    let parse_error = match error {
+                        CommandModelServiceError::Parse(error) => Some(Cow::Borrowed(error)),
+                        CommandModelServiceError::Unresolved(UnresolvedOption { field, id }) => {
+                            Some(Cow::Owned(ParseError::Option(ParseOptionError {
+                                field: field.clone(),
+                                kind: ParseOptionErrorType::LookupFailed(*id),
+                            })))
+                        }
+                        _ => None,
+                    };
+
+                    run(parse_error
+                        .map(|parse_error| hooks.parse_error(interaction, &parse_error))
+                        .into_iter()
+                        .chain([hooks.error(interaction, error)]))
+                }
+                _ => run(std::iter::empty()),
+            };
+            called.await;
+
+            result
+        })
+    }
+}
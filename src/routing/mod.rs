@@ -1,61 +1,200 @@
 pub mod command_router;
 pub mod command_service;
+pub mod component_router;
+pub mod hooks;
+pub mod route;
+pub mod route_options;
 
+use crate::request::AsInteraction;
+use crate::routing::hooks::{Completion, DispatchOutcome, Hooks};
+use crate::routing::route::{layer_names, Route, RouteKey, RouteMetadata};
+use crate::BoxFuture;
+use arc_swap::{ArcSwap, Guard};
+use std::any::type_name;
 use std::collections::HashMap;
-use std::future::Future;
-use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
-use tower::{Layer, Service};
-use twilight_model::application::interaction::Interaction;
+use std::time::Instant;
+use tower::{Layer, Service, ServiceExt};
 use twilight_model::id::marker::InteractionMarker;
 use twilight_model::id::Id;
 
-#[derive(Clone, Eq, PartialEq, Debug)]
-pub struct InteractionRouterService<Service, Layer = ()> {
+type Routes<Key, Service> = HashMap<Key, Route<Arc<Service>>>;
+
+/// Routes interactions to services by a [`RouteKey`], by default the interaction id.
+///
+/// The route table and hooks are shared between all clones of a router, including routers returned
+/// by [`layer`](Self::layer), so routes added, replaced or removed through one of them are observed
+/// atomically by every other. Layers added with `layer` are applied to a route the first time it is
+/// called, and the layered service is reused until the route is replaced. Routers are equal if
+/// they share their route table and hooks and have equal layers.
+///
+/// Routers are always ready, since the route is only known in `call`; the routed service is
+/// driven to readiness in the returned future.
+#[derive(Debug)]
+pub struct InteractionRouterService<
+    Service,
+    Layer = (),
+    Key = Id<InteractionMarker>,
+    Outer = (),
+    Base = Service,
+> {
     layer: Layer,
-    routes: HashMap<Id<InteractionMarker>, Service>,
+    layer_names: Vec<&'static str>,
+    outer: Outer,
+    outer_names: Vec<&'static str>,
+    routes: Arc<ArcSwap<Routes<Key, Base>>>,
+    layered: Arc<ArcSwap<Layered<Key, Base, Service>>>,
+    hooks: Arc<ArcSwap<Hooks>>,
+}
+
+/// Route services with the layers added by [`InteractionRouterService::layer`] applied, built from
+/// a snapshot of the route table.
+#[derive(Debug)]
+struct Layered<Key, Base, Service> {
+    routes: Arc<Routes<Key, Base>>,
+    services: HashMap<Key, (Arc<Base>, Service)>,
+}
+
+impl<Key, Base, Service> Default for Layered<Key, Base, Service> {
+    fn default() -> Self {
+        Layered {
+            routes: Arc::default(),
+            services: HashMap::new(),
+        }
+    }
+}
+
+impl<Key: RouteKey, Base: Clone, Service: Clone> Layered<Key, Base, Service> {
+    /// Layers the routes of `routes`, reusing the services of routes which weren't replaced.
+    fn update(
+        &self,
+        routes: Arc<Routes<Key, Base>>,
+        outer: &impl Layer<Base, Service = Service>,
+    ) -> Self {
+        let services = routes
+            .iter()
+            .map(|(key, route)| {
+                let service = match self.services.get(key) {
+                    Some((base, service)) if Arc::ptr_eq(base, &route.service) => service.clone(),
+                    _ => outer.layer(Base::clone(&route.service)),
+                };
+
+                (key.clone(), (Arc::clone(&route.service), service))
+            })
+            .collect();
+
+        Layered { routes, services }
+    }
+}
+
+// Manually implement Clone because the shared route table should not require Service: Clone
+impl<TService, TLayer: Clone, Key, Outer: Clone, Base> Clone
+    for InteractionRouterService<TService, TLayer, Key, Outer, Base>
+{
+    fn clone(&self) -> Self {
+        InteractionRouterService {
+            layer: self.layer.clone(),
+            layer_names: self.layer_names.clone(),
+            outer: self.outer.clone(),
+            outer_names: self.outer_names.clone(),
+            routes: Arc::clone(&self.routes),
+            layered: Arc::clone(&self.layered),
+            hooks: Arc::clone(&self.hooks),
+        }
+    }
+}
+
+impl<TService, TLayer: PartialEq, Key, Outer: PartialEq, Base> PartialEq
+    for InteractionRouterService<TService, TLayer, Key, Outer, Base>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.layer == other.layer
+            && self.outer == other.outer
+            && Arc::ptr_eq(&self.routes, &other.routes)
+            && Arc::ptr_eq(&self.hooks, &other.hooks)
+    }
 }
 
-impl<TService, Layer> Default for InteractionRouterService<TService, Layer>
+impl<TService, TLayer: Eq, Key, Outer: Eq, Base> Eq
+    for InteractionRouterService<TService, TLayer, Key, Outer, Base>
+{
+}
+
+impl<TService, Layer, Key> Default for InteractionRouterService<TService, Layer, Key>
 where
     Layer: Default,
+    Key: RouteKey,
 {
     fn default() -> Self {
-        InteractionRouterService {
-            layer: Layer::default(),
-            routes: HashMap::new(),
-        }
+        InteractionRouterService::with_layer(Layer::default())
     }
 }
 
-impl<TService, TLayer> Service<Interaction> for InteractionRouterService<TService, TLayer>
+impl<TService, TLayer, Key, Outer, Base, Request> Service<Request>
+    for InteractionRouterService<TService, TLayer, Key, Outer, Base>
 where
-    TService: Service<Interaction> + Clone + Send + 'static,
+    Key: RouteKey,
+    Outer: Layer<Base, Service = TService>,
+    Base: Clone,
+    Request: AsInteraction + Send + 'static,
+    TService: Service<Request> + Clone + Send + 'static,
     TService::Response: Send + 'static,
     TService::Error: Send + 'static,
     TService::Future: Send,
 {
     type Response = Option<TService::Response>;
     type Error = TService::Error;
-    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
 
-    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.routes
-            .values_mut()
-            .map(|service| service.poll_ready(cx))
-            .find(|elem| !matches!(elem, Poll::Ready(Ok(()))))
-            .unwrap_or(Poll::Ready(Ok(())))
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, interaction: Interaction) -> Self::Future {
-        if let Some(service) = self.routes.get_mut(&interaction.id) {
-            let clone = service.clone();
-            let mut service = std::mem::replace(service, clone);
+    fn call(&mut self, req: Request) -> Self::Future {
+        let service = Key::from_interaction(req.interaction()).and_then(|key| {
+            self.layered()
+                .services
+                .get(&key)
+                .map(|(_, service)| service.clone())
+        });
 
-            Box::pin(async move { service.call(interaction).await.map(Some) })
-        } else {
-            Box::pin(std::future::ready(Ok(None)))
+        let hooks = self.hooks.load_full();
+        if hooks.is_empty() {
+            return if let Some(service) = service {
+                Box::pin(async move { service.oneshot(req).await.map(Some) })
+            } else {
+                Box::pin(std::future::ready(Ok(None)))
+            };
         }
+
+        let interaction = req.interaction().clone();
+
+        Box::pin(async move {
+            let started = Instant::now();
+            hooks.dispatch(&interaction).await;
+
+            let result = match service {
+                Some(service) => service.oneshot(req).await.map(Some),
+                None => Ok(None),
+            };
+            let outcome = match &result {
+                Ok(Some(_)) => DispatchOutcome::Handled,
+                Ok(None) => {
+                    hooks.unmatched(&interaction).await;
+                    DispatchOutcome::Unmatched
+                }
+                Err(_) => DispatchOutcome::Failed,
+            };
+
+            let completion = Completion {
+                outcome,
+                elapsed: started.elapsed(),
+            };
+            hooks.complete(&interaction, &completion).await;
+
+            result
+        })
     }
 }
 
@@ -66,61 +205,286 @@ impl<TService> InteractionRouterService<TService> {
     }
 }
 
-impl<TService, TLayer> InteractionRouterService<TService, TLayer> {
+impl<TService, TLayer, Key: RouteKey> InteractionRouterService<TService, TLayer, Key> {
     #[must_use]
     pub fn with_layer(layer: TLayer) -> Self {
+        InteractionRouterService::with_layers(layer, ())
+    }
+}
+
+impl<TService, TLayer, Key: RouteKey, Outer, Base>
+    InteractionRouterService<TService, TLayer, Key, Outer, Base>
+{
+    /// Creates a router applying `layer` to routes when they are added and `outer` as if it was
+    /// added with [`layer`](Self::layer).
+    pub(crate) fn with_layers(layer: TLayer, outer: Outer) -> Self {
         InteractionRouterService {
             layer,
-            routes: HashMap::new(),
+            layer_names: layer_names::<TLayer>(),
+            outer,
+            outer_names: layer_names::<Outer>(),
+            routes: Arc::new(ArcSwap::from_pointee(HashMap::new())),
+            layered: Arc::new(ArcSwap::from_pointee(Layered::default())),
+            hooks: Arc::new(ArcSwap::from_pointee(Hooks::default())),
         }
     }
 
     #[must_use]
-    pub fn route<RouteService, Request>(
-        mut self,
-        id: Id<InteractionMarker>,
-        service: RouteService,
-    ) -> Self
+    pub fn route<RouteService, Request>(self, key: Key, service: RouteService) -> Self
     where
-        TLayer: Layer<RouteService, Service = TService>,
-        TService: Service<Interaction>,
+        TLayer: Layer<RouteService, Service = Base>,
+        Base: Clone,
         RouteService: Service<Request>,
     {
-        self.mut_route(id, service);
+        self.mut_route(key, service);
         self
     }
 
-    pub fn mut_route<RouteService, Request>(
-        &mut self,
-        id: Id<InteractionMarker>,
+    /// Adds or replaces the route for `key`, returning the previously routed service.
+    ///
+    /// This can be called at any time, including while clones of this router are serving
+    /// interactions.
+    pub fn mut_route<RouteService, Request>(&self, key: Key, service: RouteService) -> Option<Base>
+    where
+        TLayer: Layer<RouteService, Service = Base>,
+        Base: Clone,
+        RouteService: Service<Request>,
+    {
+        self.mut_route_with_metadata(key, service, RouteMetadata::default())
+    }
+
+    // The key is cloned into every attempt of the read-copy-update
+    #[allow(clippy::needless_pass_by_value)]
+    pub(crate) fn mut_route_with_metadata<RouteService, Request>(
+        &self,
+        key: Key,
         service: RouteService,
-    ) -> Option<TService>
+        mut metadata: RouteMetadata,
+    ) -> Option<Base>
     where
-        TLayer: Layer<RouteService, Service = TService>,
-        TService: Service<Interaction>,
+        TLayer: Layer<RouteService, Service = Base>,
+        Base: Clone,
         RouteService: Service<Request>,
     {
-        let layered = self.layer.layer(service);
-        self.routes.insert(id, layered)
+        let route = Route {
+            service: Arc::new(self.layer.layer(service)),
+            metadata: {
+                metadata.layers.extend(&self.layer_names);
+                metadata
+            },
+        };
+
+        let previous = self.routes.rcu(|routes| {
+            let mut routes = Routes::clone(routes);
+            routes.insert(key.clone(), route.clone());
+            routes
+        });
+
+        previous.get(&key).map(|route| Base::clone(&route.service))
     }
 
+    /// Removes the route for `key`, returning the previously routed service.
+    pub fn remove_route(&self, key: &Key) -> Option<Base>
+    where
+        Base: Clone,
+    {
+        let previous = self.routes.rcu(|routes| {
+            let mut routes = Routes::clone(routes);
+            routes.remove(key);
+            routes
+        });
+
+        previous.get(key).map(|route| Base::clone(&route.service))
+    }
+
+    #[must_use]
+    pub fn contains_route(&self, key: &Key) -> bool {
+        self.routes.load().contains_key(key)
+    }
+
+    /// Returns a snapshot of the registered routes and their metadata.
+    pub fn routes(&self) -> impl Iterator<Item = (Key, RouteMetadata)> {
+        let routes: Vec<_> = self
+            .routes
+            .load()
+            .iter()
+            .map(|(key, route)| {
+                let mut metadata = route.metadata.clone();
+                metadata.layers.extend(&self.outer_names);
+                (key.clone(), metadata)
+            })
+            .collect();
+
+        routes.into_iter()
+    }
+
+    /// Sets the name and description of the route for `key`, returning whether such a route exists.
+    pub fn describe_route(
+        &self,
+        key: &Key,
+        name: impl Into<String>,
+        description: impl Into<String>,
+    ) -> bool {
+        let name = name.into();
+        let description = description.into();
+
+        let previous = self.routes.rcu(|routes| {
+            let mut routes = Routes::clone(routes);
+            if let Some(route) = routes.get_mut(key) {
+                route.metadata.name = Some(name.clone());
+                route.metadata.description = Some(description.clone());
+            }
+            routes
+        });
+
+        previous.contains_key(key)
+    }
+
+    pub(crate) fn hooks(&self) -> &Arc<ArcSwap<Hooks>> {
+        &self.hooks
+    }
+
+    /// Adds hooks with `add`, for this router and every router sharing its routes.
+    pub(crate) fn add_hooks(&self, add: impl Fn(&mut Hooks)) {
+        self.hooks.rcu(|hooks| {
+            let mut hooks = Hooks::clone(hooks);
+            add(&mut hooks);
+            hooks
+        });
+    }
+
+    /// Returns the layered route services, layering routes added or replaced since the last call.
+    fn layered(&self) -> Arc<Layered<Key, Base, TService>>
+    where
+        Outer: Layer<Base, Service = TService>,
+        Base: Clone,
+        TService: Clone,
+    {
+        let mut layered = self.layered.load_full();
+
+        loop {
+            let routes = self.routes.load_full();
+            if Arc::ptr_eq(&layered.routes, &routes) {
+                return layered;
+            }
+
+            let updated = Arc::new(layered.update(routes, &self.outer));
+            let previous = self
+                .layered
+                .compare_and_swap(&layered, Arc::clone(&updated));
+            if Arc::ptr_eq(&previous, &layered) {
+                return updated;
+            }
+
+            // Another clone updated the layered routes first, so retry from its services to not
+            // layer a route twice
+            layered = Guard::into_inner(previous);
+        }
+    }
+
+    /// Wraps every route in `layer`, including routes added later through this router or any
+    /// router sharing its routes.
     #[must_use]
     pub fn layer<NewLayer>(
         self,
         layer: NewLayer,
-    ) -> InteractionRouterService<NewLayer::Service, (NewLayer, TLayer)>
+    ) -> InteractionRouterService<NewLayer::Service, TLayer, Key, (NewLayer, Outer), Base>
     where
         NewLayer: Layer<TService>,
     {
-        let routes = self
-            .routes
-            .into_iter()
-            .map(|(id, service)| (id, layer.layer(service)))
-            .collect();
+        let mut outer_names = self.outer_names;
+        outer_names.push(type_name::<NewLayer>());
 
         InteractionRouterService {
-            layer: (layer, self.layer),
-            routes,
+            layer: self.layer,
+            layer_names: self.layer_names,
+            outer: (layer, self.outer),
+            outer_names,
+            routes: self.routes,
+            layered: Arc::new(ArcSwap::from_pointee(Layered::default())),
+            hooks: self.hooks,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::routing::InteractionRouterService;
+    use crate::test_utils;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tower::layer::layer_fn;
+    use tower::util::MapResponse;
+    use tower::{service_fn, ServiceExt};
+    use twilight_model::application::interaction::Interaction;
+    use twilight_model::id::Id;
+
+    #[tokio::test]
+    async fn routes_are_shared_between_clones() {
+        let respond = |value| service_fn(move |_: Interaction| async move { Ok::<_, ()>(value) });
+
+        let router = InteractionRouterService::new().route(Id::new(1), respond(1));
+        let worker = router.clone();
+
+        let res = worker
+            .clone()
+            .oneshot(test_utils::interaction(Id::new(2)))
+            .await;
+        assert_eq!(res, Ok(None));
+
+        router.mut_route(Id::new(2), respond(2));
+        let res = worker
+            .clone()
+            .oneshot(test_utils::interaction(Id::new(2)))
+            .await;
+        assert_eq!(res, Ok(Some(2)));
+
+        assert!(router.remove_route(&Id::new(1)).is_some());
+        let res = worker
+            .clone()
+            .oneshot(test_utils::interaction(Id::new(1)))
+            .await;
+        assert_eq!(res, Ok(None));
+    }
+
+    #[tokio::test]
+    async fn layered_routers_share_routes() {
+        let respond = |value| service_fn(move |_: Interaction| async move { Ok::<_, ()>(value) });
+        let layered_count = Arc::new(AtomicUsize::new(0));
+
+        let router = InteractionRouterService::new().route(Id::new(1), respond(1));
+        let layered = router.clone().layer(layer_fn({
+            let layered_count = Arc::clone(&layered_count);
+            move |service| {
+                layered_count.fetch_add(1, Ordering::Relaxed);
+                MapResponse::new(service, |value: i64| value * 10)
+            }
+        }));
+
+        router.mut_route(Id::new(2), respond(2));
+        layered.mut_route(Id::new(3), respond(3));
+
+        let res = router
+            .clone()
+            .oneshot(test_utils::interaction(Id::new(3)))
+            .await;
+        assert_eq!(res, Ok(Some(3)));
+
+        for (id, value) in [(1, 10), (2, 20), (3, 30)] {
+            let res = layered
+                .clone()
+                .oneshot(test_utils::interaction(Id::new(id)))
+                .await;
+            assert_eq!(res, Ok(Some(value)));
+        }
+        assert_eq!(layered_count.load(Ordering::Relaxed), 3);
+
+        router.mut_route(Id::new(2), respond(4));
+        let res = layered
+            .clone()
+            .oneshot(test_utils::interaction(Id::new(2)))
+            .await;
+        assert_eq!(res, Ok(Some(40)));
+        assert_eq!(layered_count.load(Ordering::Relaxed), 4);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::installation::InstallationFilter;
+use std::any::type_name;
+use std::hash::Hash;
+use twilight_model::application::interaction::Interaction;
+use twilight_model::id::marker::InteractionMarker;
+use twilight_model::id::Id;
+
+/// Key by which an [`InteractionRouterService`](super::InteractionRouterService) looks up routes.
+pub trait RouteKey: Clone + Eq + Hash {
+    /// Returns the key of the route that should handle `interaction`, if any.
+    fn from_interaction(interaction: &Interaction) -> Option<Self>;
+}
+
+impl RouteKey for Id<InteractionMarker> {
+    fn from_interaction(interaction: &Interaction) -> Option<Self> {
+        Some(interaction.id)
+    }
+}
+
+/// Describes a registered route.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct RouteMetadata {
+    /// Name of the routed command, if it was described.
+    pub name: Option<String>,
+    /// Description of the routed command, if it was described.
+    pub description: Option<String>,
+    /// Type name of the model the route parses interactions into, if any.
+    pub model: Option<&'static str>,
+    /// Type names of the layers applied to the route, innermost first.
+    pub layers: Vec<&'static str>,
+    /// Contexts and installations the route is restricted to, if any.
+    pub installation: Option<InstallationFilter>,
+    /// Whether calls to the route are audited.
+    pub audited: bool,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Route<Service> {
+    pub(crate) service: Service,
+    pub(crate) metadata: RouteMetadata,
+}
+
+pub(crate) fn layer_names<Layer>() -> Vec<&'static str> {
+    let name = type_name::<Layer>();
+
+    if name == "()" {
+        Vec::new()
+    } else {
+        vec![name]
+    }
+}
@@ -0,0 +1,337 @@
+use crate::command_model_layer::CommandModelServiceError;
+use crate::installation::InstallationFilter;
+use crate::request::AsInteraction;
+use crate::BoxFuture;
+use std::convert::Infallible;
+use std::future::Ready;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tower::limit::rate::Rate;
+use tower::limit::{ConcurrencyLimit, ConcurrencyLimitLayer};
+use tower::timeout::{Timeout, TimeoutLayer};
+use tower::util::{service_fn, Either, ServiceFn};
+use tower::{limit, BoxError, Service, ServiceBuilder, ServiceExt};
+
+/// Limits applied to a single route.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct RouteOptions {
+    /// Fails calls with [`CommandModelServiceError::Timeout`] if they take longer than this.
+    ///
+    /// Time spent waiting for the concurrency limit in `poll_ready` does not count towards the
+    /// timeout.
+    pub timeout: Option<Duration>,
+    /// Maximum number of concurrent calls, further calls wait in `poll_ready` for a slot.
+    ///
+    /// Must be between 1 and [`Semaphore::MAX_PERMITS`].
+    pub concurrency_limit: Option<usize>,
+    /// Fails calls with [`CommandModelServiceError::RateLimited`] if the rate is exceeded.
+    pub rate_limit: Option<RateLimit>,
+    /// Fails calls with [`CommandModelServiceError::WrongInstallation`] if the interaction is not
+    /// allowed by this filter.
+    ///
+    /// The filter is also recorded in the route metadata.
+    pub installation: Option<InstallationFilter>,
+}
+
+impl RouteOptions {
+    /// Checks that the limits can be enforced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for zero limits and concurrency limits above [`Semaphore::MAX_PERMITS`].
+    pub fn validate(&self) -> Result<(), RouteOptionsError> {
+        if self.timeout == Some(Duration::ZERO) {
+            return Err(RouteOptionsError::Timeout);
+        }
+
+        if self
+            .concurrency_limit
+            .is_some_and(|limit| !(1..=Semaphore::MAX_PERMITS).contains(&limit))
+        {
+            return Err(RouteOptionsError::ConcurrencyLimit);
+        }
+
+        if self
+            .rate_limit
+            .is_some_and(|limit| limit.num == 0 || limit.per.is_zero())
+        {
+            return Err(RouteOptionsError::RateLimit);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, thiserror::Error)]
+pub enum RouteOptionsError {
+    #[error("timeout must not be zero")]
+    Timeout,
+    #[error("concurrency limit must be between 1 and {}", Semaphore::MAX_PERMITS)]
+    ConcurrencyLimit,
+    #[error("rate limit must allow at least one call per non-zero duration")]
+    RateLimit,
+}
+
+/// Allows `num` calls per `per` duration.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct RateLimit {
+    pub num: u64,
+    pub per: Duration,
+}
+
+impl RateLimit {
+    #[must_use]
+    pub fn new(num: u64, per: Duration) -> Self {
+        RateLimit { num, per }
+    }
+}
+
+/// Moves the result of `Service` into the response, so that tower's middleware, which boxes its
+/// errors, never sees the route's error.
+#[derive(Clone, Debug)]
+struct Outcome<Service>(Service);
+
+impl<TService, Request> Service<Request> for Outcome<TService>
+where
+    TService: Service<Request> + Clone + Send + 'static,
+    TService::Future: Send,
+    Request: Send + 'static,
+{
+    type Response = Result<TService::Response, TService::Error>;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let call = self.0.clone().oneshot(req);
+        Box::pin(async move { Ok(call.await) })
+    }
+}
+
+type Ticket = ServiceFn<fn(()) -> Ready<Result<(), Infallible>>>;
+
+/// A [`RateLimit`](limit::RateLimit) shared between clones, which rejects calls rather than
+/// waiting for the next period.
+#[derive(Debug)]
+struct RateGate {
+    rate: Rate,
+    // Created on first use, as it needs a runtime
+    limit: Mutex<Option<limit::RateLimit<Ticket>>>,
+}
+
+impl RateGate {
+    fn try_acquire(&self) -> bool {
+        let mut limit = self.limit.lock().unwrap_or_else(PoisonError::into_inner);
+        let limit = limit.get_or_insert_with(|| {
+            let ticket: fn(()) -> Ready<Result<(), Infallible>> = |()| std::future::ready(Ok(()));
+            limit::RateLimit::new(service_fn(ticket), self.rate)
+        });
+
+        // Nothing waits for the rate limit, so its wakeup can be dropped
+        let ready = limit
+            .poll_ready(&mut Context::from_waker(Waker::noop()))
+            .is_ready();
+        if ready {
+            drop(limit.call(()));
+        }
+
+        ready
+    }
+}
+
+type Limited<Service> = Either<ConcurrencyLimit<Timed<Service>>, Timed<Service>>;
+
+type Timed<Service> = Either<Timeout<Outcome<Service>>, Outcome<Service>>;
+
+/// Applies [`RouteOptions`] to a route.
+///
+/// The limit state is shared between clones of this service.
+#[derive(Clone, Debug)]
+pub struct RouteOptionsService<Service> {
+    inner: Limited<Service>,
+    rate: Option<Arc<RateGate>>,
+    installation: Option<Arc<InstallationFilter>>,
+}
+
+impl<Service> RouteOptionsService<Service> {
+    /// # Errors
+    ///
+    /// Returns an error if `options` are not [valid](RouteOptions::validate).
+    pub fn new(inner: Service, options: RouteOptions) -> Result<Self, RouteOptionsError> {
+        options.validate()?;
+
+        Ok(RouteOptionsService {
+            inner: ServiceBuilder::new()
+                .option_layer(options.concurrency_limit.map(ConcurrencyLimitLayer::new))
+                .option_layer(options.timeout.map(TimeoutLayer::new))
+                .service(Outcome(inner)),
+            rate: options.rate_limit.map(|limit| {
+                Arc::new(RateGate {
+                    rate: Rate::new(limit.num, limit.per),
+                    limit: Mutex::new(None),
+                })
+            }),
+            installation: options.installation.map(Arc::new),
+        })
+    }
+}
+
+impl<TService, Request, ServiceError> Service<Request> for RouteOptionsService<TService>
+where
+    TService:
+        Service<Request, Error = CommandModelServiceError<ServiceError>> + Clone + Send + 'static,
+    TService::Response: Send + 'static,
+    TService::Future: Send + 'static,
+    ServiceError: Send + 'static,
+    Request: AsInteraction + Send + 'static,
+{
+    type Response = TService::Response;
+    type Error = TService::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Only `Outcome` could fail here, and it never does
+        self.inner.poll_ready(cx).map(|_| Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        if let Some(installation) = &self.installation {
+            if !installation.allows(req.interaction()) {
+                return Box::pin(std::future::ready(Err(
+                    CommandModelServiceError::WrongInstallation,
+                )));
+            }
+        }
+
+        if self.rate.as_ref().is_some_and(|rate| !rate.try_acquire()) {
+            return Box::pin(std::future::ready(Err(
+                CommandModelServiceError::RateLimited,
+            )));
+        }
+
+        let call = self.inner.call(req);
+        Box::pin(async move {
+            // The route's own errors are part of the response, the only error left is the timeout
+            call.await.unwrap_or(Err(CommandModelServiceError::Timeout))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::command_model_layer::CommandModelServiceError;
+    use crate::routing::command_router::CommandRouterService;
+    use crate::routing::command_service::command_service;
+    use crate::routing::route_options::{
+        RateLimit, RouteOptions, RouteOptionsError, RouteOptionsService,
+    };
+    use crate::test_utils::{self, Empty};
+    use std::time::Duration;
+    use tower::{service_fn, Service, ServiceExt};
+    use twilight_model::application::interaction::Interaction;
+    use twilight_model::id::Id;
+
+    #[tokio::test]
+    async fn route_options() {
+        async fn slow(_state: (), _model: Empty) -> Result<i64, ()> {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(1)
+        }
+
+        async fn fast(_state: (), _model: Empty) -> Result<i64, ()> {
+            Ok(2)
+        }
+
+        let router = CommandRouterService::new(())
+            .route_with(
+                Id::new(1),
+                command_service(slow),
+                RouteOptions {
+                    timeout: Some(Duration::from_millis(10)),
+                    ..RouteOptions::default()
+                },
+            )
+            .route_with(
+                Id::new(2),
+                command_service(fast),
+                RouteOptions {
+                    concurrency_limit: Some(1),
+                    rate_limit: Some(RateLimit::new(1, Duration::from_secs(30))),
+                    ..RouteOptions::default()
+                },
+            );
+
+        let res = router
+            .clone()
+            .oneshot(test_utils::interaction(Id::new(1)))
+            .await;
+        assert_eq!(res, Err(CommandModelServiceError::Timeout));
+
+        let res = router
+            .clone()
+            .oneshot(test_utils::interaction(Id::new(2)))
+            .await;
+        assert_eq!(res, Ok(Some(2)));
+
+        let res = router.oneshot(test_utils::interaction(Id::new(2))).await;
+        assert_eq!(res, Err(CommandModelServiceError::RateLimited));
+    }
+
+    #[tokio::test]
+    async fn rate_limit_resets() {
+        let inner =
+            service_fn(|_: Interaction| async { Ok::<_, CommandModelServiceError<()>>(()) });
+        let options = RouteOptions {
+            rate_limit: Some(RateLimit::new(1, Duration::from_millis(20))),
+            ..RouteOptions::default()
+        };
+        let service = RouteOptionsService::new(inner, options).unwrap();
+        let call = || service.clone().oneshot(test_utils::interaction(Id::new(1)));
+
+        assert_eq!(call().await, Ok(()));
+        assert_eq!(call().await, Err(CommandModelServiceError::RateLimited));
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(call().await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit() {
+        let invalid = |concurrency_limit| {
+            RouteOptions {
+                concurrency_limit: Some(concurrency_limit),
+                ..RouteOptions::default()
+            }
+            .validate()
+        };
+        assert_eq!(invalid(0), Err(RouteOptionsError::ConcurrencyLimit));
+        assert_eq!(
+            invalid(usize::MAX),
+            Err(RouteOptionsError::ConcurrencyLimit)
+        );
+
+        let inner =
+            service_fn(|_: Interaction| async { Ok::<_, CommandModelServiceError<()>>(()) });
+        let options = RouteOptions {
+            concurrency_limit: Some(1),
+            ..RouteOptions::default()
+        };
+        let mut service = RouteOptionsService::new(inner, options).unwrap();
+        let mut other = service.clone();
+
+        let call = service
+            .ready()
+            .await
+            .unwrap()
+            .call(test_utils::interaction(Id::new(1)));
+        let waiting = tokio::time::timeout(Duration::from_millis(10), other.ready()).await;
+        assert!(waiting.is_err());
+
+        assert_eq!(call.await, Ok(()));
+        assert!(other.ready().await.is_ok());
+    }
+}
@@ -0,0 +1,526 @@
+use crate::request::AsInteraction;
+use crate::routing::component_router::{custom_id, split_custom_id, CUSTOM_ID_SEPARATOR};
+use crate::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+use tower::{Layer, Service, ServiceExt};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SessionError {
+    #[error("Session store I/O failed")]
+    Io(#[from] std::io::Error),
+    #[error("Session data could not be serialized or deserialized")]
+    Serde(#[from] serde_json::Error),
+    #[error("Custom id does not reference a session")]
+    MissingId,
+    #[error("Session was not found or has expired")]
+    NotFound,
+}
+
+/// A session as persisted by a [`SessionStore`].
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StoredSession {
+    pub data: serde_json::Value,
+    pub expires_at: SystemTime,
+}
+
+/// Persists sessions by id.
+///
+/// The returned futures fail with an error if the store could not be read or written.
+pub trait SessionStore: Send + Sync {
+    fn load<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<StoredSession>, SessionError>>;
+
+    fn save<'a>(
+        &'a self,
+        id: &'a str,
+        session: &'a StoredSession,
+    ) -> BoxFuture<'a, Result<(), SessionError>>;
+
+    fn remove<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), SessionError>>;
+}
+
+#[derive(Default, Debug)]
+pub struct MemorySessionStore {
+    sessions: Mutex<HashMap<String, StoredSession>>,
+}
+
+impl MemorySessionStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SessionStore for MemorySessionStore {
+    fn load<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<StoredSession>, SessionError>> {
+        let sessions = self.sessions.lock().unwrap_or_else(PoisonError::into_inner);
+        Box::pin(std::future::ready(Ok(sessions.get(id).cloned())))
+    }
+
+    fn save<'a>(
+        &'a self,
+        id: &'a str,
+        session: &'a StoredSession,
+    ) -> BoxFuture<'a, Result<(), SessionError>> {
+        let now = SystemTime::now();
+        let mut sessions = self.sessions.lock().unwrap_or_else(PoisonError::into_inner);
+
+        sessions.retain(|_, session| session.expires_at > now);
+        sessions.insert(id.to_owned(), session.clone());
+
+        Box::pin(std::future::ready(Ok(())))
+    }
+
+    fn remove<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), SessionError>> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(PoisonError::into_inner);
+        sessions.remove(id);
+
+        Box::pin(std::future::ready(Ok(())))
+    }
+}
+
+/// Stores every session as a JSON file in a directory.
+#[derive(Clone, Debug)]
+pub struct FileSessionStore {
+    directory: PathBuf,
+}
+
+impl FileSessionStore {
+    /// Creates a store in `directory`, creating the directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory could not be created.
+    pub fn new(directory: impl Into<PathBuf>) -> Result<Self, SessionError> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+
+        Ok(FileSessionStore { directory })
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf, SessionError> {
+        // Ids come from user controlled custom ids, so they must not be able to escape the
+        // directory
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(SessionError::NotFound);
+        }
+
+        Ok(self.directory.join(format!("{id}.json")))
+    }
+
+    /// Removes the files of expired sessions, returning how many were removed.
+    ///
+    /// Expired sessions are otherwise only removed when they are loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory could not be read or a file could not be removed.
+    pub async fn sweep(&self) -> Result<usize, SessionError> {
+        let now = SystemTime::now();
+        let mut removed = 0;
+        let mut entries = tokio::fs::read_dir(&self.directory).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+
+            // The session may have been removed in the meantime
+            let Ok(bytes) = tokio::fs::read(&path).await else {
+                continue;
+            };
+
+            if serde_json::from_slice::<StoredSession>(&bytes)
+                .is_ok_and(|session| session.expires_at <= now)
+            {
+                remove_file(path).await?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+async fn remove_file(path: PathBuf) -> Result<(), SessionError> {
+    match tokio::fs::remove_file(path).await {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error.into()),
+        _ => Ok(()),
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn load<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<StoredSession>, SessionError>> {
+        Box::pin(async move {
+            match tokio::fs::read(self.path(id)?).await {
+                Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(error) => Err(error.into()),
+            }
+        })
+    }
+
+    fn save<'a>(
+        &'a self,
+        id: &'a str,
+        session: &'a StoredSession,
+    ) -> BoxFuture<'a, Result<(), SessionError>> {
+        Box::pin(async move {
+            tokio::fs::write(self.path(id)?, serde_json::to_vec(session)?).await?;
+            Ok(())
+        })
+    }
+
+    fn remove<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), SessionError>> {
+        Box::pin(async move { remove_file(self.path(id)?).await })
+    }
+}
+
+/// State kept between an interaction and subsequent component interactions.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Session<T> {
+    pub id: String,
+    pub expires_at: SystemTime,
+    pub data: T,
+}
+
+impl<T> Session<T> {
+    /// Returns a custom id routed to the component route `route` referencing this session.
+    #[must_use]
+    pub fn custom_id(&self, route: &str) -> String {
+        format!("{route}{CUSTOM_ID_SEPARATOR}{}", self.id)
+    }
+
+    /// Like [`Session::custom_id`], but with an additional action the handler can distinguish.
+    #[must_use]
+    pub fn custom_id_with_action(&self, route: &str, action: &str) -> String {
+        format!(
+            "{route}{CUSTOM_ID_SEPARATOR}{}{CUSTOM_ID_SEPARATOR}{action}",
+            self.id
+        )
+    }
+}
+
+/// Returns the session id and action referenced by a component custom id.
+#[must_use]
+pub fn session_id(custom_id: &str) -> Option<(&str, Option<&str>)> {
+    let (_route, rest) = split_custom_id(custom_id);
+
+    rest.map(|rest| match rest.split_once(CUSTOM_ID_SEPARATOR) {
+        Some((id, action)) => (id, Some(action)),
+        None => (rest, None),
+    })
+}
+
+pub(crate) fn generate_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    if let Ok(elapsed) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(elapsed.as_nanos());
+    }
+
+    format!("{:016x}", hasher.finish())
+}
+
+/// Creates and loads typed sessions from a [`SessionStore`].
+#[derive(Debug)]
+pub struct Sessions<Store> {
+    store: Arc<Store>,
+}
+
+impl<Store> Clone for Sessions<Store> {
+    fn clone(&self) -> Self {
+        Sessions {
+            store: Arc::clone(&self.store),
+        }
+    }
+}
+
+impl<Store: SessionStore> Sessions<Store> {
+    #[must_use]
+    pub fn new(store: Store) -> Self {
+        Sessions {
+            store: Arc::new(store),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the data could not be serialized or the store failed.
+    pub async fn create<T: Serialize>(
+        &self,
+        data: T,
+        ttl: Duration,
+    ) -> Result<Session<T>, SessionError> {
+        let session = Session {
+            id: generate_id(),
+            expires_at: SystemTime::now() + ttl,
+            data,
+        };
+        self.save(&session).await?;
+
+        Ok(session)
+    }
+
+    /// Loads the session `id`, returning `None` if it does not exist or has expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data could not be deserialized or the store failed.
+    pub async fn load<T: DeserializeOwned>(
+        &self,
+        id: &str,
+    ) -> Result<Option<Session<T>>, SessionError> {
+        let Some(stored) = self.store.load(id).await? else {
+            return Ok(None);
+        };
+
+        if stored.expires_at <= SystemTime::now() {
+            self.store.remove(id).await?;
+            return Ok(None);
+        }
+
+        Ok(Some(Session {
+            id: id.to_owned(),
+            expires_at: stored.expires_at,
+            data: serde_json::from_value(stored.data)?,
+        }))
+    }
+
+    /// Saves changes to the data or expiry of `session`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data could not be serialized or the store failed.
+    pub async fn save<T: Serialize>(&self, session: &Session<T>) -> Result<(), SessionError> {
+        let stored = StoredSession {
+            data: serde_json::to_value(&session.data)?,
+            expires_at: session.expires_at,
+        };
+
+        self.store.save(&session.id, &stored).await
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the store failed.
+    pub async fn remove(&self, id: &str) -> Result<(), SessionError> {
+        self.store.remove(id).await
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SessionServiceError<ServiceError> {
+    #[error("Error loading session")]
+    Session(#[from] SessionError),
+    #[error("Inner service error")]
+    Service(ServiceError),
+}
+
+/// Loads the session referenced by a component custom id and passes it to the inner service.
+#[derive(Debug)]
+pub struct SessionLayer<T, Store> {
+    sessions: Sessions<Store>,
+    phantom_data: PhantomData<fn() -> T>,
+}
+
+// Manually implement Clone because T should have no bearing on the implementation
+impl<T, Store> Clone for SessionLayer<T, Store> {
+    fn clone(&self) -> Self {
+        SessionLayer {
+            sessions: self.sessions.clone(),
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<T, Store> SessionLayer<T, Store> {
+    #[must_use]
+    pub fn new(sessions: Sessions<Store>) -> Self {
+        SessionLayer {
+            sessions,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<T, Store, TService> Layer<TService> for SessionLayer<T, Store> {
+    type Service = SessionService<TService, T, Store>;
+
+    fn layer(&self, inner: TService) -> Self::Service {
+        SessionService {
+            inner,
+            sessions: self.sessions.clone(),
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SessionService<Service, T, Store> {
+    inner: Service,
+    sessions: Sessions<Store>,
+    phantom_data: PhantomData<fn() -> T>,
+}
+
+// Manually implement Clone because T should have no bearing on the implementation
+impl<Service: Clone, T, Store> Clone for SessionService<Service, T, Store> {
+    fn clone(&self) -> Self {
+        SessionService {
+            inner: self.inner.clone(),
+            sessions: self.sessions.clone(),
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<TService, T, Store, Request> Service<Request> for SessionService<TService, T, Store>
+where
+    TService: Service<(Session<T>, Request)> + Clone + Send + 'static,
+    TService::Response: Send + 'static,
+    TService::Error: Send + 'static,
+    TService::Future: Send,
+    T: DeserializeOwned + Send + 'static,
+    Store: SessionStore + 'static,
+    Request: AsInteraction + Send + 'static,
+{
+    type Response = TService::Response;
+    type Error = SessionServiceError<TService::Error>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // The inner service is driven to readiness after the session was loaded
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let Some((id, _action)) = custom_id(req.interaction()).and_then(session_id) else {
+            return Box::pin(std::future::ready(Err(SessionError::MissingId.into())));
+        };
+        let id = id.to_owned();
+        let sessions = self.sessions.clone();
+        let inner = self.inner.clone();
+
+        Box::pin(async move {
+            let session = sessions.load(&id).await?.ok_or(SessionError::NotFound)?;
+
+            inner
+                .oneshot((session, req))
+                .await
+                .map_err(SessionServiceError::Service)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::routing::component_router::ComponentRouterService;
+    use crate::session::{
+        FileSessionStore, MemorySessionStore, Session, SessionError, SessionLayer,
+        SessionServiceError, SessionStore, Sessions,
+    };
+    use crate::test_utils;
+    use std::time::Duration;
+    use tower::{service_fn, Layer, ServiceExt};
+    use twilight_model::application::interaction::Interaction;
+
+    #[tokio::test]
+    async fn component_router_loads_sessions() {
+        let sessions = Sessions::new(MemorySessionStore::new());
+        let session = sessions
+            .create(3_u32, Duration::from_secs(90))
+            .await
+            .unwrap();
+        let expired = sessions.create(4_u32, Duration::ZERO).await.unwrap();
+
+        let router = ComponentRouterService::new().route(
+            "page",
+            SessionLayer::new(sessions).layer(service_fn(
+                |(session, _interaction): (Session<u32>, Interaction)| async move {
+                    Ok::<_, ()>(session.data)
+                },
+            )),
+        );
+
+        let res = router
+            .clone()
+            .oneshot(test_utils::component_interaction(
+                &session.custom_id_with_action("page", "next"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res, Some(3));
+
+        let res = router
+            .clone()
+            .oneshot(test_utils::component_interaction(
+                &expired.custom_id("page"),
+            ))
+            .await;
+        assert!(matches!(
+            res,
+            Err(SessionServiceError::Session(SessionError::NotFound))
+        ));
+
+        let res = router
+            .oneshot(test_utils::component_interaction("other"))
+            .await;
+        assert!(matches!(res, Ok(None)));
+    }
+
+    #[tokio::test]
+    async fn file_store() {
+        let directory =
+            std::env::temp_dir().join(format!("kubar-sparkles-sessions-{}", std::process::id()));
+        let store = FileSessionStore::new(&directory).unwrap();
+        let sessions = Sessions::new(store.clone());
+
+        let session = sessions
+            .create(vec!["a".to_owned()], Duration::from_secs(90))
+            .await
+            .unwrap();
+        let loaded = sessions.load::<Vec<String>>(&session.id).await.unwrap();
+        assert_eq!(loaded, Some(session.clone()));
+
+        sessions.remove(&session.id).await.unwrap();
+        assert_eq!(
+            sessions.load::<Vec<String>>(&session.id).await.unwrap(),
+            None
+        );
+
+        assert!(matches!(
+            store.load("../escape").await,
+            Err(SessionError::NotFound)
+        ));
+
+        let expired = sessions.create((), Duration::ZERO).await.unwrap();
+        sessions.create((), Duration::from_secs(90)).await.unwrap();
+        std::fs::write(directory.join("notes.txt"), "not a session").unwrap();
+        assert_eq!(store.sweep().await.unwrap(), 1);
+        assert_eq!(store.load(&expired.id).await.unwrap(), None);
+        assert!(directory.join("notes.txt").exists());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
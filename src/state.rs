@@ -2,12 +2,40 @@ use std::marker::PhantomData;
 use std::task::{Context, Poll};
 use tower::{Layer, Service};
 
-#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
-pub struct StateLayer<Request, State> {
+/// Derives a value from a reference to a larger state.
+///
+/// This allows handlers to only depend on the part of the router state they need, e.g. a database
+/// pool or config, instead of cloning the whole application state per call.
+pub trait FromRef<T> {
+    fn from_ref(input: &T) -> Self;
+}
+
+impl<T: Clone> FromRef<T> for T {
+    fn from_ref(input: &T) -> Self {
+        input.clone()
+    }
+}
+
+// TODO: manually impl rest of derive traits
+#[derive(Ord, PartialOrd, Eq, PartialEq, Debug)]
+pub struct StateLayer<Request, State, SubState = State> {
     state: State,
-    phantom_data: PhantomData<Request>,
+    phantom_data: PhantomData<fn() -> (Request, SubState)>,
 }
 
+// Manually implement derive traits because Request and SubState generic params should have no
+// bearing on implementations
+impl<Request, State: Clone, SubState> Clone for StateLayer<Request, State, SubState> {
+    fn clone(&self) -> Self {
+        StateLayer {
+            state: self.state.clone(),
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<Request, State: Copy, SubState> Copy for StateLayer<Request, State, SubState> {}
+
 impl<Request, State> StateLayer<Request, State> {
     #[must_use]
     pub fn new(state: State) -> Self {
@@ -18,42 +46,76 @@ impl<Request, State> StateLayer<Request, State> {
     }
 }
 
-impl<Request, State, TService> Layer<TService> for StateLayer<Request, State>
+impl<Request, State, SubState> StateLayer<Request, State, SubState>
+where
+    SubState: FromRef<State>,
+{
+    /// Creates a layer that passes the part of `state` given by [`FromRef`] to the inner service.
+    #[must_use]
+    pub fn with_substate(state: State) -> Self {
+        StateLayer {
+            state,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<Request, State, SubState, TService> Layer<TService> for StateLayer<Request, State, SubState>
 where
     State: Clone,
-    TService: Service<(State, Request)>,
+    SubState: FromRef<State>,
+    TService: Service<(SubState, Request)>,
 {
-    type Service = StateLayerService<State, TService>;
+    type Service = StateLayerService<State, TService, SubState>;
 
     fn layer(&self, inner: TService) -> Self::Service {
         StateLayerService {
             state: self.state.clone(),
             inner,
+            phantom_data: PhantomData,
         }
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-pub struct StateLayerService<State, TService> {
+// TODO: manually impl rest of derive traits
+#[derive(Eq, PartialEq, Debug)]
+pub struct StateLayerService<State, TService, SubState = State> {
     state: State,
     inner: TService,
+    phantom_data: PhantomData<fn() -> SubState>,
 }
 
-impl<State, TService, Request, Response, Error> Service<Request>
-    for StateLayerService<State, TService>
+// Manually implement derive traits because SubState generic param should have no bearing on
+// implementations
+impl<State: Clone, TService: Clone, SubState> Clone
+    for StateLayerService<State, TService, SubState>
+{
+    fn clone(&self) -> Self {
+        StateLayerService {
+            state: self.state.clone(),
+            inner: self.inner.clone(),
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<State: Copy, TService: Copy, SubState> Copy for StateLayerService<State, TService, SubState> {}
+
+impl<State, TService, SubState, Request, Response, Error> Service<Request>
+    for StateLayerService<State, TService, SubState>
 where
-    State: Clone,
-    TService: Service<(State, Request), Response = Response, Error = Error>,
+    SubState: FromRef<State>,
+    TService: Service<(SubState, Request), Response = Response, Error = Error>,
 {
     type Response = Response;
     type Error = Error;
-    type Future = <TService as Service<(State, Request)>>::Future;
+    type Future = <TService as Service<(SubState, Request)>>::Future;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         self.inner.poll_ready(cx)
     }
 
     fn call(&mut self, req: Request) -> Self::Future {
-        self.inner.call((self.state.clone(), req))
+        self.inner.call((SubState::from_ref(&self.state), req))
     }
 }
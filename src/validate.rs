@@ -0,0 +1,437 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use twilight_model::application::command::{
+    Command, CommandOption, CommandOptionChoiceValue, CommandOptionType, CommandOptionValue,
+    CommandType,
+};
+
+pub const NAME_MAX_LENGTH: usize = 32;
+pub const DESCRIPTION_MAX_LENGTH: usize = 100;
+pub const OPTIONS_MAX: usize = 25;
+pub const CHOICES_MAX: usize = 25;
+pub const CHOICE_MAX_LENGTH: usize = 100;
+pub const STRING_MAX_LENGTH: u16 = 6000;
+pub const GLOBAL_CHAT_INPUT_COMMANDS_MAX: usize = 100;
+/// Maximum number of global user commands, and separately of global message commands.
+pub const GLOBAL_CONTEXT_MENU_COMMANDS_MAX: usize = 15;
+
+#[derive(Clone, Eq, PartialEq, Debug, thiserror::Error)]
+pub enum ValidationErrorKind {
+    #[error("name must be 1 to 32 letters, numbers, dashes or underscores")]
+    InvalidName,
+    #[error("name must be 1 to 32 characters")]
+    NameLength,
+    #[error("description must be 1 to 100 characters")]
+    DescriptionLength,
+    #[error("at most 25 options are allowed")]
+    TooManyOptions,
+    #[error("at most 25 choices are allowed")]
+    TooManyChoices,
+    #[error("subcommands can only be nested in subcommand groups, which can only be top level")]
+    InvalidNesting,
+    #[error("chat input command and option names must be lowercase")]
+    UppercaseName,
+    #[error("name {0} is used more than once")]
+    DuplicateName(String),
+    #[error("required options must come before optional ones")]
+    RequiredAfterOptional,
+    #[error("at most {max} global {kind} commands are allowed")]
+    TooManyCommands { kind: &'static str, max: usize },
+    #[error("choice names and string values must be 1 to 100 characters")]
+    ChoiceLength,
+    #[error("minimum must not be greater than maximum")]
+    InvalidRange,
+    #[error("length limits must be between 0 and 6000, and the maximum at least 1")]
+    InvalidLengthLimit,
+}
+
+/// A violated constraint, with the path to the offending field.
+///
+/// Paths index into the list of commands and their options, e.g. `[0].options[2].choices[1]`.
+/// Errors about the list as a whole have an empty path.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ValidationError {
+    pub path: String,
+    pub kind: ValidationErrorKind,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{}: {}", self.path, self.kind)
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Checks `commands` against Discord's limits for command definitions.
+///
+/// # Errors
+///
+/// Returns every violated constraint.
+pub fn validate_commands(commands: &[Command]) -> Result<(), Vec<ValidationError>> {
+    let mut errors = vec![];
+
+    for (kind, name, max) in [
+        (
+            CommandType::ChatInput,
+            "chat input",
+            GLOBAL_CHAT_INPUT_COMMANDS_MAX,
+        ),
+        (CommandType::User, "user", GLOBAL_CONTEXT_MENU_COMMANDS_MAX),
+        (
+            CommandType::Message,
+            "message",
+            GLOBAL_CONTEXT_MENU_COMMANDS_MAX,
+        ),
+    ] {
+        let count = commands
+            .iter()
+            .filter(|command| command.kind == kind && command.guild_id.is_none())
+            .count();
+
+        if count > max {
+            errors.push(ValidationError {
+                path: String::new(),
+                kind: ValidationErrorKind::TooManyCommands { kind: name, max },
+            });
+        }
+    }
+
+    // Names only need to be unique per command type and guild
+    let mut names = HashSet::new();
+    for (index, command) in commands.iter().enumerate() {
+        if !names.insert((command.kind, command.guild_id, command.name.as_str())) {
+            errors.push(ValidationError {
+                path: format!("[{index}].name"),
+                kind: ValidationErrorKind::DuplicateName(command.name.clone()),
+            });
+        }
+    }
+
+    for (index, command) in commands.iter().enumerate() {
+        validate_command(command, &format!("[{index}]"), &mut errors);
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn validate_command(command: &Command, path: &str, errors: &mut Vec<ValidationError>) {
+    let mut error = |field: &str, kind| {
+        errors.push(ValidationError {
+            path: format!("{path}.{field}"),
+            kind,
+        });
+    };
+
+    // Context menu command names may contain spaces and capitals
+    if command.kind != CommandType::ChatInput {
+        if !is_valid_name_length(&command.name) {
+            error("name", ValidationErrorKind::NameLength);
+        }
+    } else if !is_valid_name(&command.name) {
+        error("name", ValidationErrorKind::InvalidName);
+    } else if has_uppercase(&command.name) {
+        error("name", ValidationErrorKind::UppercaseName);
+    }
+
+    // User and message commands have empty descriptions
+    if command.kind == CommandType::ChatInput && !is_valid_description(&command.description) {
+        error("description", ValidationErrorKind::DescriptionLength);
+    }
+
+    if command.options.len() > OPTIONS_MAX {
+        error("options", ValidationErrorKind::TooManyOptions);
+    }
+
+    validate_options(&command.options, None, path, errors);
+}
+
+fn validate_options(
+    options: &[CommandOption],
+    parent: Option<CommandOptionType>,
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut names = HashSet::new();
+    let mut seen_optional = false;
+
+    for (index, option) in options.iter().enumerate() {
+        let option_path = format!("{path}.options[{index}]");
+
+        if !names.insert(option.name.as_str()) {
+            errors.push(ValidationError {
+                path: format!("{option_path}.name"),
+                kind: ValidationErrorKind::DuplicateName(option.name.clone()),
+            });
+        }
+
+        // Subcommands and groups have no required flag
+        if !matches!(
+            option.kind,
+            CommandOptionType::SubCommand | CommandOptionType::SubCommandGroup
+        ) {
+            if option.required == Some(true) {
+                if seen_optional {
+                    errors.push(ValidationError {
+                        path: format!("{option_path}.required"),
+                        kind: ValidationErrorKind::RequiredAfterOptional,
+                    });
+                }
+            } else {
+                seen_optional = true;
+            }
+        }
+
+        validate_option(option, parent, &option_path, errors);
+    }
+}
+
+fn validate_option(
+    option: &CommandOption,
+    parent: Option<CommandOptionType>,
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut error = |field: &str, kind| {
+        errors.push(ValidationError {
+            path: if field.is_empty() {
+                path.to_owned()
+            } else {
+                format!("{path}.{field}")
+            },
+            kind,
+        });
+    };
+
+    if !is_valid_name(&option.name) {
+        error("name", ValidationErrorKind::InvalidName);
+    } else if has_uppercase(&option.name) {
+        error("name", ValidationErrorKind::UppercaseName);
+    }
+
+    if !is_valid_description(&option.description) {
+        error("description", ValidationErrorKind::DescriptionLength);
+    }
+
+    let nesting_allowed = match option.kind {
+        CommandOptionType::SubCommandGroup => parent.is_none(),
+        CommandOptionType::SubCommand => {
+            matches!(parent, None | Some(CommandOptionType::SubCommandGroup))
+        }
+        _ => parent != Some(CommandOptionType::SubCommandGroup),
+    };
+    if !nesting_allowed {
+        error("", ValidationErrorKind::InvalidNesting);
+    }
+
+    let choices = option.choices.as_deref().unwrap_or_default();
+    if choices.len() > CHOICES_MAX {
+        error("choices", ValidationErrorKind::TooManyChoices);
+    }
+
+    for (index, choice) in choices.iter().enumerate() {
+        let value_too_long = match &choice.value {
+            CommandOptionChoiceValue::String(value) => {
+                value.is_empty() || value.chars().count() > CHOICE_MAX_LENGTH
+            }
+            _ => false,
+        };
+
+        if !(1..=CHOICE_MAX_LENGTH).contains(&choice.name.chars().count()) || value_too_long {
+            error(
+                &format!("choices[{index}]"),
+                ValidationErrorKind::ChoiceLength,
+            );
+        }
+    }
+
+    if let (Some(min), Some(max)) = (option.min_value, option.max_value) {
+        let inverted = match (min, max) {
+            (CommandOptionValue::Integer(min), CommandOptionValue::Integer(max)) => min > max,
+            (min, max) => value_as_f64(min) > value_as_f64(max),
+        };
+
+        if inverted {
+            error("min_value", ValidationErrorKind::InvalidRange);
+        }
+    }
+
+    if option.min_length.is_some_and(|min| min > STRING_MAX_LENGTH) {
+        error("min_length", ValidationErrorKind::InvalidLengthLimit);
+    }
+    if option
+        .max_length
+        .is_some_and(|max| max == 0 || max > STRING_MAX_LENGTH)
+    {
+        error("max_length", ValidationErrorKind::InvalidLengthLimit);
+    }
+    if let (Some(min), Some(max)) = (option.min_length, option.max_length) {
+        if min > max {
+            error("min_length", ValidationErrorKind::InvalidRange);
+        }
+    }
+
+    let options = option.options.as_deref().unwrap_or_default();
+    if options.len() > OPTIONS_MAX {
+        error("options", ValidationErrorKind::TooManyOptions);
+    }
+
+    validate_options(options, Some(option.kind), path, errors);
+}
+
+// Integer bounds beyond 2^53 lose precision, which doesn't matter for comparing them
+#[allow(clippy::cast_precision_loss)]
+fn value_as_f64(value: CommandOptionValue) -> f64 {
+    match value {
+        CommandOptionValue::Integer(value) => value as f64,
+        CommandOptionValue::Number(value) => value,
+    }
+}
+
+fn has_uppercase(name: &str) -> bool {
+    name.chars().any(|char| char.to_lowercase().ne([char]))
+}
+
+fn is_valid_name_length(name: &str) -> bool {
+    (1..=NAME_MAX_LENGTH).contains(&name.chars().count())
+}
+
+fn is_valid_name(name: &str) -> bool {
+    is_valid_name_length(name)
+        && name
+            .chars()
+            .all(|char| char.is_alphanumeric() || char == '-' || char == '_')
+}
+
+fn is_valid_description(description: &str) -> bool {
+    (1..=DESCRIPTION_MAX_LENGTH).contains(&description.chars().count())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::validate::{validate_commands, ValidationError, ValidationErrorKind};
+    use twilight_interactions::command::{CommandModel, CreateCommand};
+    use twilight_model::application::command::{Command, CommandOptionType, CommandType};
+
+    #[derive(CommandModel, CreateCommand)]
+    #[command(name = "ban", desc = "Bans a user")]
+    struct Ban {
+        /// Reason for the ban
+        _reason: Option<String>,
+    }
+
+    #[test]
+    fn validates_commands() {
+        let valid: Command = Ban::create_command().into();
+        assert_eq!(validate_commands(std::slice::from_ref(&valid)), Ok(()));
+
+        let mut invalid = valid.clone();
+        invalid.name = "not valid".to_owned();
+        invalid.options[0].description = "x".repeat(101);
+        invalid.options[0].options = Some(vec![valid.options[0].clone()]);
+        invalid.options[0].kind = CommandOptionType::SubCommandGroup;
+
+        let errors = validate_commands(&[valid, invalid]).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError {
+                    path: "[1].name".to_owned(),
+                    kind: ValidationErrorKind::InvalidName,
+                },
+                ValidationError {
+                    path: "[1].options[0].description".to_owned(),
+                    kind: ValidationErrorKind::DescriptionLength,
+                },
+                ValidationError {
+                    path: "[1].options[0].options[0]".to_owned(),
+                    kind: ValidationErrorKind::InvalidNesting,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_limits() {
+        let valid: Command = Ban::create_command().into();
+
+        let mut invalid = valid.clone();
+        invalid.name = "Ban".to_owned();
+        let mut required = valid.options[0].clone();
+        required.name = "user".to_owned();
+        required.required = Some(true);
+        required.min_length = Some(10);
+        required.max_length = Some(5);
+        invalid.options.push(required.clone());
+        invalid.options.push(required);
+
+        let mut commands = vec![valid; 101];
+        for (index, command) in commands.iter_mut().enumerate().skip(1) {
+            command.name = format!("ban{index}");
+        }
+        commands.push(invalid);
+
+        let errors = validate_commands(&commands).unwrap_err();
+        let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "at most 100 global chat input commands are allowed",
+                "[101].name: chat input command and option names must be lowercase",
+                "[101].options[1].required: required options must come before optional ones",
+                "[101].options[1].min_length: minimum must not be greater than maximum",
+                "[101].options[2].name: name user is used more than once",
+                "[101].options[2].required: required options must come before optional ones",
+                "[101].options[2].min_length: minimum must not be greater than maximum",
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_context_menu_names() {
+        let mut command: Command = Ban::create_command().into();
+        command.kind = CommandType::Message;
+        command.name = "Report Message".to_owned();
+        command.description = String::new();
+        command.options = vec![];
+        assert_eq!(validate_commands(std::slice::from_ref(&command)), Ok(()));
+
+        let mut commands = vec![command.clone(); 15];
+        for (index, command) in commands.iter_mut().enumerate() {
+            command.name = format!("Report {index}");
+        }
+        let mut user = commands.clone();
+        for command in &mut user {
+            command.kind = CommandType::User;
+        }
+        commands.extend(user);
+        assert_eq!(validate_commands(&commands), Ok(()));
+
+        commands.push(command.clone());
+        let errors = validate_commands(&commands).unwrap_err();
+        assert_eq!(
+            errors[0].kind,
+            ValidationErrorKind::TooManyCommands {
+                kind: "message",
+                max: 15
+            }
+        );
+
+        command.name = "x".repeat(33);
+        assert_eq!(
+            validate_commands(&[command]),
+            Err(vec![ValidationError {
+                path: "[0].name".to_owned(),
+                kind: ValidationErrorKind::NameLength,
+            }])
+        );
+    }
+}